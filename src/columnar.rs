@@ -0,0 +1,299 @@
+use pyo3::exceptions::PyImportError;
+use pyo3::prelude::*;
+use pyo3::types::{PyByteArray, PyBytes, PyDict, PyList};
+
+use lindera::token::Token;
+
+/// NumPy dtype of the string columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringDtype {
+    // Fixed width `<U` arrays, padded to the longest value of the column
+    Unicode,
+    // Arrays of Python `str` objects
+    Object,
+}
+
+impl StringDtype {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "unicode" => Ok(StringDtype::Unicode),
+            "object" => Ok(StringDtype::Object),
+            _ => Err(format!(
+                "unknown string dtype '{name}', expected one of unicode, object"
+            )),
+        }
+    }
+}
+
+/// Column of strings stored as one UTF-8 buffer and the offsets of every value in it.
+#[derive(Debug)]
+pub struct StringColumn {
+    offsets: Vec<i64>,
+    data: String,
+}
+
+impl StringColumn {
+    fn new() -> Self {
+        StringColumn {
+            offsets: vec![0],
+            data: String::new(),
+        }
+    }
+
+    fn push(&mut self, value: &str) {
+        self.data.push_str(value);
+        self.offsets.push(self.data.len() as i64);
+    }
+
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn append(&mut self, other: &StringColumn) {
+        let base = self.data.len() as i64;
+        self.data.push_str(&other.data);
+        self.offsets
+            .extend(other.offsets[1..].iter().map(|offset| base + offset));
+    }
+
+    fn values(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.offsets
+            .windows(2)
+            .map(|range| &self.data[range[0] as usize..range[1] as usize])
+    }
+
+    // Encode as fixed width UTF-32, the layout of a NumPy `<U` array,
+    // where every value is padded with zeros to the length of the longest one
+    fn to_utf32(&self) -> (usize, Vec<u8>) {
+        let width = self
+            .values()
+            .map(|value| value.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let mut buffer = vec![0u8; self.len() * width * 4];
+        for (i, value) in self.values().enumerate() {
+            for (j, c) in value.chars().enumerate() {
+                let offset = (i * width + j) * 4;
+                buffer[offset..offset + 4].copy_from_slice(&(c as u32).to_le_bytes());
+            }
+        }
+
+        (width, buffer)
+    }
+}
+
+/// Tokens of several documents laid out as columns.
+#[derive(Debug)]
+pub struct TokenColumns {
+    // Number of documents pushed so far, the doc id of the next one
+    doc_count: usize,
+    doc_ids: Vec<i64>,
+    surfaces: StringColumn,
+    byte_starts: Vec<i64>,
+    byte_ends: Vec<i64>,
+    positions: Vec<i64>,
+    // Name, index into the details and values of every requested detail field
+    details: Vec<(String, usize, StringColumn)>,
+}
+
+impl TokenColumns {
+    pub fn new(fields: Vec<(String, usize)>) -> Self {
+        TokenColumns {
+            doc_count: 0,
+            doc_ids: Vec::new(),
+            surfaces: StringColumn::new(),
+            byte_starts: Vec::new(),
+            byte_ends: Vec::new(),
+            positions: Vec::new(),
+            details: fields
+                .into_iter()
+                .map(|(name, index)| (name, index, StringColumn::new()))
+                .collect(),
+        }
+    }
+
+    /// Creates empty columns with the same detail fields.
+    pub fn empty(&self) -> Self {
+        Self::new(
+            self.details
+                .iter()
+                .map(|(name, index, _)| (name.clone(), *index))
+                .collect(),
+        )
+    }
+
+    /// Adds the tokens of the next document, copying only the selected details.
+    pub fn push(&mut self, tokens: &mut [Token<'_>]) {
+        let doc_id = self.doc_count as i64;
+        self.doc_count += 1;
+        for token in tokens {
+            self.doc_ids.push(doc_id);
+            self.surfaces.push(&token.text);
+            self.byte_starts.push(token.byte_start as i64);
+            self.byte_ends.push(token.byte_end as i64);
+            self.positions.push(token.position as i64);
+            if self.details.is_empty() {
+                continue;
+            }
+            let details = token.details();
+            for (_, index, column) in &mut self.details {
+                column.push(details.get(*index).copied().unwrap_or("*"));
+            }
+        }
+    }
+
+    /// Adds the documents of `other` after those of `self`.
+    pub fn append(&mut self, other: TokenColumns) {
+        let base = self.doc_count as i64;
+        self.doc_count += other.doc_count;
+        self.doc_ids
+            .extend(other.doc_ids.iter().map(|doc_id| base + doc_id));
+        self.surfaces.append(&other.surfaces);
+        self.byte_starts.extend(other.byte_starts);
+        self.byte_ends.extend(other.byte_ends);
+        self.positions.extend(other.positions);
+        for ((_, _, column), (_, _, other)) in self.details.iter_mut().zip(&other.details) {
+            column.append(other);
+        }
+    }
+
+    // Columns in output order
+    fn columns(&self) -> Vec<(&str, Column<'_>)> {
+        let mut columns = vec![
+            ("doc_id", Column::Int(&self.doc_ids)),
+            ("surface", Column::Str(&self.surfaces)),
+            ("byte_start", Column::Int(&self.byte_starts)),
+            ("byte_end", Column::Int(&self.byte_ends)),
+            ("position", Column::Int(&self.positions)),
+        ];
+        columns.extend(
+            self.details
+                .iter()
+                .map(|(name, _, column)| (name.as_str(), Column::Str(column))),
+        );
+
+        columns
+    }
+
+    /// Builds a `pyarrow.RecordBatch` from the buffers of the columns.
+    pub fn to_arrow<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let pa = import_module(py, "pyarrow")?;
+        let length = self.doc_ids.len();
+        let py_buffer = |data: &[u8]| pa.call_method1("py_buffer", (PyBytes::new(py, data),));
+
+        let mut names = Vec::new();
+        let mut arrays = Vec::new();
+        for (name, column) in self.columns() {
+            let (data_type, buffers) = match column {
+                Column::Int(values) => (
+                    pa.call_method0("int64")?,
+                    (py.None(), py_buffer(&i64_bytes(values))?).into_pyobject(py)?,
+                ),
+                Column::Str(column) => (
+                    pa.call_method0("large_string")?,
+                    (
+                        py.None(),
+                        py_buffer(&i64_bytes(&column.offsets))?,
+                        py_buffer(column.data.as_bytes())?,
+                    )
+                        .into_pyobject(py)?,
+                ),
+            };
+            names.push(name);
+            arrays.push(
+                pa.getattr("Array")?
+                    .call_method1("from_buffers", (data_type, length, buffers))?,
+            );
+        }
+
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("names", names)?;
+        pa.getattr("RecordBatch")?
+            .call_method("from_arrays", (arrays,), Some(&kwargs))
+    }
+
+    /// Builds a dict of NumPy arrays, `int64` for numbers and `string_dtype` for strings.
+    pub fn to_numpy<'py>(
+        &self,
+        py: Python<'py>,
+        string_dtype: StringDtype,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let np = import_module(py, "numpy")?;
+
+        let arrays = PyDict::new(py);
+        for (name, column) in self.columns() {
+            // A bytearray keeps the arrays writable
+            let (buffer, dtype) = match (column, string_dtype) {
+                (Column::Int(values), _) => (i64_bytes(values), "<i8".to_string()),
+                (Column::Str(column), StringDtype::Unicode) => {
+                    let (width, buffer) = column.to_utf32();
+                    (buffer, format!("<U{width}"))
+                }
+                (Column::Str(column), StringDtype::Object) => {
+                    let values = PyList::new(py, column.values())?;
+                    arrays.set_item(name, np.call_method1("array", (values, "object"))?)?;
+                    continue;
+                }
+            };
+            let array = np.call_method1("frombuffer", (PyByteArray::new(py, &buffer), dtype))?;
+            arrays.set_item(name, array)?;
+        }
+
+        Ok(arrays)
+    }
+}
+
+enum Column<'a> {
+    Int(&'a [i64]),
+    Str(&'a StringColumn),
+}
+
+fn i64_bytes(values: &[i64]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn import_module<'py>(py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyModule>> {
+    py.import(name).map_err(|err| {
+        PyImportError::new_err(format!(
+            "Failed to import {name}: {err}. Install it with `pip install {name}`"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_column() {
+        let mut column = StringColumn::new();
+        column.push("東京");
+        column.push("");
+        column.push("a");
+        assert_eq!(column.offsets, vec![0, 6, 6, 7]);
+        assert_eq!(column.values().collect::<Vec<_>>(), vec!["東京", "", "a"]);
+
+        let (width, buffer) = column.to_utf32();
+        assert_eq!(width, 2);
+        assert_eq!(buffer.len(), 3 * 2 * 4);
+        assert_eq!(&buffer[0..4], &('東' as u32).to_le_bytes());
+        assert_eq!(&buffer[16..20], &('a' as u32).to_le_bytes());
+        assert_eq!(&buffer[20..24], &[0, 0, 0, 0]);
+
+        let mut other = StringColumn::new();
+        other.push("bc");
+        column.append(&other);
+        assert_eq!(column.offsets, vec![0, 6, 6, 7, 9]);
+        assert_eq!(column.values().last(), Some("bc"));
+    }
+
+    #[test]
+    fn test_string_dtype() {
+        assert_eq!(StringDtype::from_name("object"), Ok(StringDtype::Object));
+        assert!(StringDtype::from_name("bytes").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::{Serializer, Value, json};
+
+use lindera::mode::{Mode, Penalty};
+
+// Internal helper function to create the configuration of a new TokenizerBuilder,
+// loading it from LINDERA_CONFIG_PATH when the variable is set
+pub fn default_config() -> PyResult<Value> {
+    match env::var("LINDERA_CONFIG_PATH") {
+        Ok(config_path) => load_config_file(Path::new(&config_path)),
+        Err(_) => Ok(json!({
+            "segmenter": {},
+            "character_filters": [],
+            "token_filters": []
+        })),
+    }
+}
+
+pub fn load_config_file(file_path: &Path) -> PyResult<Value> {
+    ensure_keys(read_config_file(file_path)?)
+        .map_err(|err| PyValueError::new_err(format!("Failed to load config: {err}")))
+}
+
+// Read a YAML (or JSON) config file without filling in absent sections
+pub fn read_config_file(file_path: &Path) -> PyResult<Value> {
+    let buffer = fs::read_to_string(file_path).map_err(|err| {
+        PyValueError::new_err(format!(
+            "Failed to load config from file: {}: {err}",
+            file_path.display()
+        ))
+    })?;
+
+    parse_yaml_config(&buffer)
+}
+
+pub fn parse_yaml_config(yaml: &str) -> PyResult<Value> {
+    let value = serde_yaml::from_str::<serde_yaml::Value>(yaml)
+        .map_err(|err| PyValueError::new_err(format!("Failed to parse YAML config: {err}")))?;
+    if !value.is_mapping() {
+        return Err(PyValueError::new_err(
+            "Failed to parse YAML config: config must be a mapping",
+        ));
+    }
+
+    serde_json::to_value(value)
+        .map_err(|err| PyValueError::new_err(format!("Failed to convert YAML config: {err}")))
+}
+
+pub fn parse_json_config(json: &str) -> PyResult<Value> {
+    let value = serde_json::from_str::<Value>(json)
+        .map_err(|err| PyValueError::new_err(format!("Failed to parse JSON config: {err}")))?;
+    if !value.is_object() {
+        return Err(PyValueError::new_err(
+            "Failed to parse JSON config: config must be an object",
+        ));
+    }
+
+    Ok(value)
+}
+
+// A segmenter accepts either its own section or a whole tokenizer configuration
+pub fn segmenter_section(config: Value) -> Value {
+    match config {
+        Value::Object(mut map) if map.contains_key("segmenter") => map.remove("segmenter").unwrap(),
+        config => config,
+    }
+}
+
+// Fill in the sections that are absent from a tokenizer configuration
+pub fn ensure_keys(config: Value) -> Result<Value, String> {
+    let Value::Object(mut map) = config else {
+        return Err("config must be an object".to_string());
+    };
+    map.entry("segmenter").or_insert_with(|| json!({}));
+    map.entry("character_filters").or_insert_with(|| json!([]));
+    map.entry("token_filters").or_insert_with(|| json!([]));
+
+    Ok(Value::Object(map))
+}
+
+// Serialize a mode the way it is written in a config file.
+// The default penalty is left implicit so that the common case stays a plain string.
+pub fn mode_to_value(mode: &Mode) -> Value {
+    match mode {
+        Mode::Normal => json!("normal"),
+        Mode::Decompose(penalty) if *penalty == Penalty::default() => json!("decompose"),
+        Mode::Decompose(penalty) => json!({ "decompose": penalty }),
+    }
+}
+
+pub fn config_to_yaml(config: &Value) -> PyResult<String> {
+    serde_yaml::to_string(config)
+        .map_err(|err| PyValueError::new_err(format!("Failed to serialize config to YAML: {err}")))
+}
+
+pub fn config_to_json(config: &Value, indent: Option<usize>) -> PyResult<String> {
+    let result = match indent {
+        Some(indent) => {
+            let indent = " ".repeat(indent);
+            let mut buffer = Vec::new();
+            let mut serializer = Serializer::with_formatter(
+                &mut buffer,
+                PrettyFormatter::with_indent(indent.as_bytes()),
+            );
+            config
+                .serialize(&mut serializer)
+                .map(|_| String::from_utf8_lossy(&buffer).into_owned())
+        }
+        None => serde_json::to_string(config),
+    };
+
+    result
+        .map_err(|err| PyValueError::new_err(format!("Failed to serialize config to JSON: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_to_value() {
+        assert_eq!(mode_to_value(&Mode::Normal), json!("normal"));
+        assert_eq!(
+            mode_to_value(&Mode::Decompose(Penalty::default())),
+            json!("decompose")
+        );
+
+        let penalty = Penalty {
+            kanji_penalty_length_threshold: 3,
+            ..Penalty::default()
+        };
+        let value = mode_to_value(&Mode::Decompose(penalty.clone()));
+        assert_eq!(
+            serde_json::from_value::<Mode>(value).unwrap(),
+            Mode::Decompose(penalty)
+        );
+    }
+
+    #[test]
+    fn test_segmenter_section() {
+        let segmenter = json!({ "mode": "normal", "dictionary": "embedded://ipadic" });
+        assert_eq!(segmenter_section(segmenter.clone()), segmenter);
+        assert_eq!(
+            segmenter_section(json!({ "segmenter": segmenter.clone(), "token_filters": [] })),
+            segmenter
+        );
+    }
+
+    #[test]
+    fn test_ensure_keys() {
+        let config =
+            ensure_keys(json!({ "segmenter": { "dictionary": "embedded://ipadic" } })).unwrap();
+        assert_eq!(config["segmenter"]["dictionary"], "embedded://ipadic");
+        assert_eq!(config["character_filters"], json!([]));
+        assert_eq!(config["token_filters"], json!([]));
+
+        assert!(ensure_keys(json!(["segmenter"])).is_err());
+    }
+}
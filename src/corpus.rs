@@ -0,0 +1,301 @@
+use std::sync::Arc;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use lindera::dictionary::Schema;
+
+use crate::schema::PySchema;
+use crate::token::PyToken;
+
+// Schema of tokens read from CoNLL-U, naming the columns after FORM
+const CONLLU_FIELDS: &[&str] = &[
+    "surface",
+    "left_context_id",
+    "right_context_id",
+    "cost",
+    "lemma",
+    "upos",
+    "xpos",
+    "feats",
+    "head",
+    "deprel",
+    "deps",
+    "misc",
+];
+
+// Tokens of one gold sentence, laid out over the text rebuilt from their surfaces,
+// of which only the length is tracked
+struct SentenceBuilder {
+    byte_len: usize,
+    char_len: usize,
+    tokens: Vec<PyToken>,
+}
+
+impl SentenceBuilder {
+    fn new() -> Self {
+        SentenceBuilder {
+            byte_len: 0,
+            char_len: 0,
+            tokens: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, surface: &str, details: Vec<String>, schema: Option<&Arc<Schema>>) {
+        let byte_start = self.byte_len;
+        let char_start = self.char_len;
+        self.byte_len += surface.len();
+        self.char_len += surface.chars().count();
+
+        self.tokens.push(PyToken::from_surface(
+            surface,
+            byte_start,
+            char_start,
+            self.tokens.len(),
+            details,
+            schema.cloned(),
+        ));
+    }
+
+    fn push_space(&mut self) {
+        self.byte_len += 1;
+        self.char_len += 1;
+    }
+
+    fn finish(&mut self, sentences: &mut Vec<Vec<PyToken>>) {
+        if !self.tokens.is_empty() {
+            sentences.push(std::mem::take(&mut self.tokens));
+        }
+        self.byte_len = 0;
+        self.char_len = 0;
+    }
+}
+
+/// Parses the default output of MeCab, `surface\tfeatures` lines with `EOS` after every sentence.
+///
+/// Features are comma separated, and may be quoted with `"` when they contain commas.
+/// Offsets refer to the sentence rebuilt by joining the surfaces.
+pub fn read_mecab(text: &str, schema: Option<&Arc<Schema>>) -> Result<Vec<Vec<PyToken>>, String> {
+    let mut sentences = Vec::new();
+    let mut sentence = SentenceBuilder::new();
+    for (number, line) in text.lines().enumerate() {
+        if line == "EOS" {
+            sentence.finish(&mut sentences);
+            continue;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (surface, features) = line
+            .split_once('\t')
+            .ok_or_else(|| format!("line {}: expected 'surface\\tfeatures'", number + 1))?;
+        sentence.push(surface, split_features(features), schema);
+    }
+    sentence.finish(&mut sentences);
+
+    Ok(sentences)
+}
+
+/// Parses CoNLL-U, one sentence per block of lines separated by a blank line.
+///
+/// The details of a token are the columns LEMMA, UPOS, XPOS, FEATS, HEAD, DEPREL, DEPS and MISC,
+/// named `lemma`, `upos` and so on in the schema of the token.
+/// A multiword token such as `1-2` is read as one token in place of its words, and empty nodes are skipped.
+/// Offsets refer to the sentence rebuilt from the forms, with a space after every token but those marked `SpaceAfter=No`.
+pub fn read_conllu(text: &str) -> Result<Vec<Vec<PyToken>>, String> {
+    let schema = Arc::new(Schema::new(
+        CONLLU_FIELDS
+            .iter()
+            .map(|field| field.to_string())
+            .collect(),
+    ));
+    let mut sentences = Vec::new();
+    let mut sentence = SentenceBuilder::new();
+    // Last word id covered by the current multiword token
+    let mut covered = 0;
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            sentence.finish(&mut sentences);
+            covered = 0;
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let columns = line.split('\t').collect::<Vec<_>>();
+        if columns.len() != 10 {
+            return Err(format!(
+                "line {}: expected 10 columns, found {}",
+                number + 1,
+                columns.len()
+            ));
+        }
+        let id = columns[0];
+        if id.contains('.') {
+            continue;
+        }
+        if let Some((_, last)) = id.split_once('-') {
+            covered = last
+                .parse()
+                .map_err(|_| format!("line {}: invalid token id '{id}'", number + 1))?;
+        } else {
+            let id: usize = id
+                .parse()
+                .map_err(|_| format!("line {}: invalid token id '{id}'", number + 1))?;
+            if id <= covered {
+                continue;
+            }
+        }
+
+        sentence.push(
+            columns[1],
+            columns[2..]
+                .iter()
+                .map(|column| column.to_string())
+                .collect(),
+            Some(&schema),
+        );
+        let space_after = !columns[9].split('|').any(|item| item == "SpaceAfter=No");
+        if space_after {
+            sentence.push_space();
+        }
+    }
+    sentence.finish(&mut sentences);
+
+    Ok(sentences)
+}
+
+/// Parses wakati text, one sentence per line with the tokens separated by whitespace.
+///
+/// Offsets refer to the sentence rebuilt by joining the surfaces without the separators.
+pub fn read_wakati(text: &str) -> Vec<Vec<PyToken>> {
+    let mut sentences = Vec::new();
+    let mut sentence = SentenceBuilder::new();
+    for line in text.lines() {
+        for surface in line.split_whitespace() {
+            sentence.push(surface, Vec::new(), None);
+        }
+        sentence.finish(&mut sentences);
+    }
+
+    sentences
+}
+
+// Split comma separated features, keeping commas inside double quotes
+fn split_features(features: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = features.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted || field.is_empty() => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+/// Reads MeCab output into a list of sentences of `Token`.
+///
+/// `schema` names the features, so that `Token.get` and `Token.upos` work on the gold tokens.
+#[pyfunction]
+#[pyo3(signature = (text, schema=None))]
+pub fn parse_mecab(text: &str, schema: Option<PySchema>) -> PyResult<Vec<Vec<PyToken>>> {
+    let schema = schema.map(|schema| Arc::new(Schema::from(schema)));
+    read_mecab(text, schema.as_ref())
+        .map_err(|err| PyValueError::new_err(format!("Failed to parse MeCab output: {err}")))
+}
+
+/// Reads CoNLL-U into a list of sentences of `Token`.
+#[pyfunction]
+#[pyo3(signature = (text))]
+pub fn parse_conllu(text: &str) -> PyResult<Vec<Vec<PyToken>>> {
+    read_conllu(text)
+        .map_err(|err| PyValueError::new_err(format!("Failed to parse CoNLL-U: {err}")))
+}
+
+/// Reads wakati text into a list of sentences of `Token`.
+#[pyfunction]
+#[pyo3(signature = (text))]
+pub fn parse_wakati(text: &str) -> Vec<Vec<PyToken>> {
+    read_wakati(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(sentence: &[PyToken]) -> Vec<(&str, usize, usize)> {
+        sentence
+            .iter()
+            .map(|token| {
+                (
+                    token.text.as_str(),
+                    token.char_start.unwrap(),
+                    token.char_end.unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_mecab() {
+        let sentences = read_mecab(
+            "東京\t名詞,固有名詞,地域,一般,*,*,東京,トウキョウ,トーキョー\nに\t助詞,格助詞\nEOS\n\"\t記号,\"a,b\",\"\"\"\"\nEOS\n",
+            None,
+        )
+        .unwrap();
+        assert_eq!(sentences.len(), 2);
+        assert_eq!(spans(&sentences[0]), vec![("東京", 0, 2), ("に", 2, 3)]);
+        assert_eq!(sentences[0][1].byte_start, 6);
+        assert_eq!(sentences[0][1].position, 1);
+        assert_eq!(sentences[0][0].details[7], "トウキョウ");
+        assert_eq!(sentences[1][0].details, vec!["記号", "a,b", "\""]);
+
+        assert!(read_mecab("東京 名詞\nEOS\n", None).is_err());
+    }
+
+    #[test]
+    fn test_parse_conllu() {
+        let sentences = read_conllu(
+            "# text = Let's go\n\
+             1-2\tLet's\t_\t_\t_\t_\t_\t_\t_\t_\n\
+             1\tLet\tlet\tVERB\t_\t_\t0\troot\t_\tSpaceAfter=No\n\
+             2\t's\twe\tPRON\t_\t_\t1\tobj\t_\t_\n\
+             3\tgo\tgo\tVERB\t_\t_\t1\txcomp\t_\tSpaceAfter=No\n\
+             3.1\tx\t_\t_\t_\t_\t_\t_\t_\t_\n\
+             \n\
+             1\t東京\t東京\tPROPN\t名詞-固有名詞\t_\t_\t_\t_\tSpaceAfter=No\n\
+             2\tに\tに\tADP\t助詞-格助詞\t_\t_\t_\t_\tSpaceAfter=No\n",
+        )
+        .unwrap();
+        assert_eq!(sentences.len(), 2);
+        assert_eq!(spans(&sentences[0]), vec![("Let's", 0, 5), ("go", 6, 8)]);
+        assert_eq!(spans(&sentences[1]), vec![("東京", 0, 2), ("に", 2, 3)]);
+        assert_eq!(sentences[1][0].details[1], "PROPN");
+        let schema = sentences[1][0].schema.as_ref().unwrap();
+        assert_eq!(schema.get_field_index("upos"), Some(5));
+        assert_eq!(sentences[1][1].upos().as_deref(), Some("ADP"));
+
+        assert!(read_conllu("1\t東京\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_wakati() {
+        let sentences = read_wakati("東京 に 行く\n\n今日  は\n");
+        assert_eq!(sentences.len(), 2);
+        assert_eq!(
+            spans(&sentences[0]),
+            vec![("東京", 0, 2), ("に", 2, 3), ("行く", 3, 5)]
+        );
+        assert_eq!(spans(&sentences[1]), vec![("今日", 0, 2), ("は", 2, 3)]);
+    }
+}
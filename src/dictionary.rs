@@ -1,5 +1,8 @@
+use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 
+use pyo3::types::{PyBytes, PyType};
 use pyo3::{exceptions::PyValueError, prelude::*};
 
 use lindera::dictionary::{
@@ -7,13 +10,24 @@ use lindera::dictionary::{
     load_dictionary as lindera_load_dictionary,
     load_user_dictionary as lindera_load_user_dictionary,
 };
+use lindera_dictionary::decompress::{CompressedData, decompress};
+use lindera_dictionary::dictionary::character_definition::CharacterDefinition;
+use lindera_dictionary::dictionary::connection_cost_matrix::ConnectionCostMatrix;
+use lindera_dictionary::dictionary::prefix_dictionary::PrefixDictionary;
+use lindera_dictionary::dictionary::unknown_dictionary::UnknownDictionary;
 
 use crate::metadata::PyMetadata;
 
-#[pyclass(name = "Dictionary")]
+#[pyclass(name = "Dictionary", module = "lindera")]
 #[derive(Clone)]
 pub struct PyDictionary {
     pub inner: Dictionary,
+    // URI the dictionary was loaded from
+    #[pyo3(get)]
+    pub uri: String,
+    // Decompressed connection cost matrix, which Lindera keeps private, held for pickling
+    // a dictionary that was not embedded
+    costs: Option<Arc<Vec<u8>>>,
 }
 
 #[pymethods]
@@ -37,19 +51,106 @@ impl PyDictionary {
     fn __repr__(&self) -> String {
         "Dictionary()".to_string()
     }
+
+    // A dictionary loaded from a directory is pickled by value, while an embedded dictionary,
+    // which is compiled into the module, is reloaded from its URI
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, Bound<'py, PyAny>)> {
+        let py = slf.py();
+        let this = slf.borrow();
+        if this.uri.starts_with("embedded://") {
+            let load = py.import("lindera")?.getattr("load_dictionary")?;
+            return Ok((load, (this.uri.clone(),).into_pyobject(py)?.into_any()));
+        }
+
+        let data = this
+            .serialize()
+            .map_err(|e| PyValueError::new_err(format!("Failed to serialize dictionary: {e}")))?;
+        Ok((
+            slf.get_type().getattr("_from_state")?,
+            (PyBytes::new(py, &data), this.uri.clone())
+                .into_pyobject(py)?
+                .into_any(),
+        ))
+    }
+
+    #[classmethod]
+    fn _from_state(_cls: &Bound<'_, PyType>, data: &[u8], uri: &str) -> PyResult<Self> {
+        let ((prefix_dictionary, character_definition, unknown_dictionary, metadata, costs), _): (
+            (
+                PrefixDictionary,
+                CharacterDefinition,
+                UnknownDictionary,
+                Metadata,
+                Vec<u8>,
+            ),
+            _,
+        ) = bincode::serde::decode_from_slice(data, bincode::config::legacy())
+            .map_err(|e| PyValueError::new_err(format!("Failed to deserialize dictionary: {e}")))?;
+
+        let dictionary = Dictionary {
+            prefix_dictionary,
+            connection_cost_matrix: ConnectionCostMatrix::load(costs.clone()),
+            character_definition,
+            unknown_dictionary,
+            metadata,
+        };
+        Ok(PyDictionary::new(dictionary, uri, Some(Arc::new(costs))))
+    }
 }
 
 impl PyDictionary {
     // Internal helper function to create PyDictionary from Lindera Dictionary
-    pub fn new(dictionary: Dictionary) -> Self {
-        Self { inner: dictionary }
+    pub fn new(dictionary: Dictionary, uri: &str, costs: Option<Arc<Vec<u8>>>) -> Self {
+        Self {
+            inner: dictionary,
+            uri: uri.to_string(),
+            costs,
+        }
+    }
+
+    // Serialize the dictionary with bincode, from memory only
+    fn serialize(&self) -> Result<Vec<u8>, String> {
+        let costs = self
+            .costs
+            .as_deref()
+            .ok_or("the connection cost matrix was not kept when the dictionary was loaded")?;
+
+        let dictionary = &self.inner;
+        bincode::serde::encode_to_vec(
+            (
+                &dictionary.prefix_dictionary,
+                &dictionary.character_definition,
+                &dictionary.unknown_dictionary,
+                &dictionary.metadata,
+                costs,
+            ),
+            bincode::config::legacy(),
+        )
+        .map_err(|e| e.to_string())
     }
 }
 
-#[pyclass(name = "UserDictionary")]
+// Read the connection cost matrix of a dictionary directory the way Lindera loads it,
+// which decompresses it as Lindera is built with the `compress` feature
+fn read_costs(uri: &str) -> Result<Vec<u8>, String> {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    let matrix = fs::read(Path::new(path).join("matrix.mtx"))
+        .map_err(|e| format!("failed to read the connection cost matrix: {e}"))?;
+    let (compressed, _): (CompressedData, _) =
+        bincode::serde::decode_from_slice(&matrix, bincode::config::legacy())
+            .map_err(|e| format!("failed to deserialize the connection cost matrix: {e}"))?;
+
+    decompress(compressed)
+        .map_err(|e| format!("failed to decompress the connection cost matrix: {e}"))
+}
+
+#[pyclass(name = "UserDictionary", module = "lindera")]
 #[derive(Clone)]
 pub struct PyUserDictionary {
     pub inner: UserDictionary,
+    // URI the user dictionary was loaded from
+    #[pyo3(get)]
+    pub uri: String,
 }
 
 #[pymethods]
@@ -61,13 +162,40 @@ impl PyUserDictionary {
     fn __repr__(&self) -> String {
         "UserDictionary()".to_string()
     }
+
+    // The user dictionary is pickled by value
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> PyResult<(Bound<'py, PyAny>, (Bound<'py, PyBytes>, String))> {
+        let this = slf.borrow();
+        let data =
+            bincode::serde::encode_to_vec(&this.inner, bincode::config::legacy()).map_err(|e| {
+                PyValueError::new_err(format!("Failed to serialize user dictionary: {e}"))
+            })?;
+
+        Ok((
+            slf.get_type().getattr("_from_state")?,
+            (PyBytes::new(slf.py(), &data), this.uri.clone()),
+        ))
+    }
+
+    #[classmethod]
+    fn _from_state(_cls: &Bound<'_, PyType>, data: &[u8], uri: &str) -> PyResult<Self> {
+        UserDictionary::load(data)
+            .map_err(|e| {
+                PyValueError::new_err(format!("Failed to deserialize user dictionary: {e}"))
+            })
+            .map(|user_dictionary| PyUserDictionary::new(user_dictionary, uri))
+    }
 }
 
 impl PyUserDictionary {
     // Internal helper function to create PyUserDictionary from Lindera UserDictionary
-    pub fn new(user_dictionary: UserDictionary) -> Self {
+    pub fn new(user_dictionary: UserDictionary, uri: &str) -> Self {
         Self {
             inner: user_dictionary,
+            uri: uri.to_string(),
         }
     }
 }
@@ -132,9 +260,20 @@ pub fn build_user_dictionary(
 #[pyfunction]
 #[pyo3(signature = (uri))]
 pub fn load_dictionary(uri: &str) -> PyResult<PyDictionary> {
-    lindera_load_dictionary(uri)
-        .map_err(|e| PyValueError::new_err(format!("Failed to load dictionary from '{uri}': {e}")))
-        .map(PyDictionary::new)
+    let dictionary = lindera_load_dictionary(uri).map_err(|e| {
+        PyValueError::new_err(format!("Failed to load dictionary from '{uri}': {e}"))
+    })?;
+    // An embedded dictionary is pickled by its URI and needs no copy of the matrix
+    let costs = if uri.starts_with("embedded://") {
+        None
+    } else {
+        let costs = read_costs(uri).map_err(|e| {
+            PyValueError::new_err(format!("Failed to load dictionary from '{uri}': {e}"))
+        })?;
+        Some(Arc::new(costs))
+    };
+
+    Ok(PyDictionary::new(dictionary, uri, costs))
 }
 
 #[pyfunction]
@@ -145,5 +284,5 @@ pub fn load_user_dictionary(uri: &str, metadata: PyMetadata) -> PyResult<PyUserD
         .map_err(|e| {
             PyValueError::new_err(format!("Failed to load user dictionary from '{uri}': {e}"))
         })
-        .map(PyUserDictionary::new)
+        .map(|user_dictionary| PyUserDictionary::new(user_dictionary, uri))
 }
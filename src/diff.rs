@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::schema::detail_index;
+use crate::token::{PyToken, TextIndex};
+use crate::tokenizer::PyTokenizer;
+
+/// Span of a text that two tokenizers analyze differently, with the tokens of both side by side.
+#[pyclass(name = "DiffSpan", module = "lindera")]
+#[derive(Clone)]
+pub struct PyDiffSpan {
+    // Index of the text in the compared texts
+    #[pyo3(get)]
+    pub index: usize,
+    #[pyo3(get)]
+    pub text: String,
+    #[pyo3(get)]
+    pub byte_start: usize,
+    #[pyo3(get)]
+    pub byte_end: usize,
+    #[pyo3(get)]
+    pub char_start: usize,
+    #[pyo3(get)]
+    pub char_end: usize,
+    #[pyo3(get)]
+    pub tokens_a: Vec<PyToken>,
+    #[pyo3(get)]
+    pub tokens_b: Vec<PyToken>,
+    // Fields whose values differ when both tokenizers agree on the segmentation
+    #[pyo3(get)]
+    pub fields: Vec<String>,
+}
+
+#[pymethods]
+impl PyDiffSpan {
+    /// Whether the tokenizers split the span differently, rather than only disagreeing on fields.
+    #[getter]
+    pub fn is_segmentation_diff(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    fn __repr__(&self) -> String {
+        let surfaces = |tokens: &[PyToken]| {
+            tokens
+                .iter()
+                .map(|token| token.text.as_str())
+                .collect::<Vec<_>>()
+                .join("|")
+        };
+        format!(
+            "DiffSpan(index={}, text='{}', byte_start={}, byte_end={}, a='{}', b='{}', fields={:?})",
+            self.index,
+            self.text,
+            self.byte_start,
+            self.byte_end,
+            surfaces(&self.tokens_a),
+            surfaces(&self.tokens_b),
+            self.fields
+        )
+    }
+}
+
+/// Differences between the tokens of two tokenizers over the same texts.
+#[pyclass(name = "TokenizationDiff", module = "lindera")]
+#[derive(Clone, Default)]
+pub struct PyTokenizationDiff {
+    #[pyo3(get)]
+    pub fields: Vec<String>,
+    #[pyo3(get)]
+    pub text_count: usize,
+    #[pyo3(get)]
+    pub token_count_a: usize,
+    #[pyo3(get)]
+    pub token_count_b: usize,
+    // Aligned spans, the smallest spans whose boundaries are token boundaries of both tokenizers
+    #[pyo3(get)]
+    pub span_count: usize,
+    #[pyo3(get)]
+    pub segmentation_diff_count: usize,
+    // Spans with the same segmentation but a different value, per field
+    #[pyo3(get)]
+    pub field_diff_counts: HashMap<String, usize>,
+    #[pyo3(get)]
+    pub spans: Vec<PyDiffSpan>,
+}
+
+#[pymethods]
+impl PyTokenizationDiff {
+    fn __repr__(&self) -> String {
+        format!(
+            "TokenizationDiff(text_count={}, span_count={}, segmentation_diff_count={}, field_diff_counts={:?})",
+            self.text_count, self.span_count, self.segmentation_diff_count, self.field_diff_counts
+        )
+    }
+}
+
+impl PyTokenizationDiff {
+    /// Aligns the tokens of both tokenizers for one text and adds the spans where they differ.
+    pub fn add_text(
+        &mut self,
+        index: usize,
+        text: &str,
+        tokens_a: &[PyToken],
+        tokens_b: &[PyToken],
+    ) {
+        let tokens_a = sorted_words(tokens_a);
+        let tokens_b = sorted_words(tokens_b);
+        let text_index = TextIndex::new(text, false);
+        self.text_count += 1;
+        self.token_count_a += tokens_a.len();
+        self.token_count_b += tokens_b.len();
+
+        let (mut i, mut j) = (0, 0);
+        while i < tokens_a.len() || j < tokens_b.len() {
+            // A span starts at the first remaining token and grows over every token that overlaps it
+            let start = tokens_a
+                .get(i)
+                .into_iter()
+                .chain(tokens_b.get(j))
+                .map(|token| token.byte_start)
+                .min()
+                .unwrap_or_default();
+            let mut end = start;
+            let (first_a, first_b) = (i, j);
+            loop {
+                let mut grown = false;
+                while let Some(token) = tokens_a.get(i).filter(|token| {
+                    token.byte_start < end || (i == first_a && token.byte_start == start)
+                }) {
+                    end = end.max(token.byte_end);
+                    i += 1;
+                    grown = true;
+                }
+                while let Some(token) = tokens_b.get(j).filter(|token| {
+                    token.byte_start < end || (j == first_b && token.byte_start == start)
+                }) {
+                    end = end.max(token.byte_end);
+                    j += 1;
+                    grown = true;
+                }
+                if !grown {
+                    break;
+                }
+            }
+            self.span_count += 1;
+
+            let (span_a, span_b) = (&tokens_a[first_a..i], &tokens_b[first_b..j]);
+            let same_segmentation = span_a.len() == 1
+                && span_b.len() == 1
+                && span_a[0].byte_start == span_b[0].byte_start
+                && span_a[0].byte_end == span_b[0].byte_end;
+            let fields = if same_segmentation {
+                let fields = self
+                    .fields
+                    .iter()
+                    .filter(|field| span_a[0].field_value(field) != span_b[0].field_value(field))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if fields.is_empty() {
+                    continue;
+                }
+                for field in &fields {
+                    *self.field_diff_counts.entry(field.clone()).or_default() += 1;
+                }
+                fields
+            } else {
+                self.segmentation_diff_count += 1;
+                Vec::new()
+            };
+
+            self.spans.push(PyDiffSpan {
+                index,
+                text: text.get(start..end).unwrap_or_default().to_string(),
+                byte_start: start,
+                byte_end: end,
+                char_start: text_index.char_offset(start),
+                char_end: text_index.char_offset(end),
+                tokens_a: span_a.iter().map(|token| (*token).clone()).collect(),
+                tokens_b: span_b.iter().map(|token| (*token).clone()).collect(),
+                fields,
+            });
+        }
+    }
+}
+
+// Tokens in text order, without the whitespace tokens that some dictionaries emit
+fn sorted_words(tokens: &[PyToken]) -> Vec<&PyToken> {
+    let mut words = tokens
+        .iter()
+        .filter(|token| !token.text.trim().is_empty())
+        .collect::<Vec<_>>();
+    words.sort_by_key(|token| (token.byte_start, token.byte_end));
+
+    words
+}
+
+/// Tokenizes `texts` with both tokenizers and reports the spans where they differ.
+///
+/// Tokens are aligned into the smallest spans whose boundaries are token boundaries of both tokenizers.
+/// A span differs when the tokenizers split it differently, or when they agree on a single token
+/// but one of `fields` has a different value, such as `"reading"` or `"upos"`.
+/// Fields are looked up by name in the schema of each tokenizer, so that tokenizers
+/// with different dictionaries can be compared on the fields they share.
+#[pyfunction]
+#[pyo3(signature = (tokenizer_a, tokenizer_b, texts, fields=None))]
+pub fn diff_tokenizations(
+    py: Python<'_>,
+    tokenizer_a: PyRef<'_, PyTokenizer>,
+    tokenizer_b: PyRef<'_, PyTokenizer>,
+    texts: Vec<String>,
+    fields: Option<Vec<String>>,
+) -> PyResult<PyTokenizationDiff> {
+    let fields = fields.unwrap_or_default();
+    for field in fields.iter().filter(|field| *field != "upos") {
+        for schema in [&tokenizer_a.schema, &tokenizer_b.schema] {
+            detail_index(schema, field).map_err(|err| {
+                PyValueError::new_err(format!("Failed to diff tokenizations: {err}"))
+            })?;
+        }
+    }
+
+    let (tokenizer_a, tokenizer_b) = (&*tokenizer_a, &*tokenizer_b);
+    py.detach(|| {
+        let mut diff = PyTokenizationDiff {
+            fields,
+            ..Default::default()
+        };
+        for (index, text) in texts.iter().enumerate() {
+            let tokens_a = tokenizer_a.tokenize_text(text)?;
+            let tokens_b = tokenizer_b.tokenize_text(text)?;
+            diff.add_text(index, text, &tokens_a, &tokens_b);
+        }
+
+        Ok(diff)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use lindera::dictionary::Schema;
+
+    use super::*;
+
+    fn tokens(surfaces: &[(&str, &str)]) -> Vec<PyToken> {
+        let schema = Arc::new(Schema::new(
+            [
+                "surface",
+                "left_context_id",
+                "right_context_id",
+                "cost",
+                "reading",
+            ]
+            .iter()
+            .map(|field| field.to_string())
+            .collect(),
+        ));
+        let mut offset = 0;
+        surfaces
+            .iter()
+            .map(|(surface, reading)| {
+                let mut token = PyToken::for_test(surface, offset, vec![reading.to_string()]);
+                token.schema = Some(schema.clone());
+                offset += surface.len();
+                token
+            })
+            .collect()
+    }
+
+    fn spans(diff: &PyTokenizationDiff) -> Vec<(&str, usize, usize, usize)> {
+        diff.spans
+            .iter()
+            .map(|span| {
+                (
+                    span.text.as_str(),
+                    span.tokens_a.len(),
+                    span.tokens_b.len(),
+                    span.fields.len(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_add_text() {
+        let text = "abcdef g";
+        let tokens_a = tokens(&[
+            ("ab", "x"),
+            ("c", "y"),
+            ("de", "z"),
+            ("f", "w"),
+            (" ", ""),
+            ("g", "v"),
+        ]);
+        let tokens_b = tokens(&[
+            ("a", "x"),
+            ("bc", "y"),
+            ("d", "z"),
+            ("ef", "w"),
+            (" ", ""),
+            ("g", "u"),
+        ]);
+
+        let mut diff = PyTokenizationDiff {
+            fields: vec!["reading".to_string()],
+            ..Default::default()
+        };
+        diff.add_text(0, text, &tokens_a, &tokens_b);
+        assert_eq!(
+            spans(&diff),
+            vec![("abc", 2, 2, 0), ("def", 2, 2, 0), ("g", 1, 1, 1)]
+        );
+        assert_eq!((diff.token_count_a, diff.token_count_b), (5, 5));
+        assert_eq!(diff.span_count, 3);
+        assert_eq!(diff.segmentation_diff_count, 2);
+        assert_eq!(diff.field_diff_counts.get("reading"), Some(&1));
+        assert!(diff.spans[0].is_segmentation_diff());
+        assert!(!diff.spans[2].is_segmentation_diff());
+
+        // Identical analyses have no spans
+        let mut diff = PyTokenizationDiff::default();
+        diff.add_text(0, text, &tokens_a, &tokens_a);
+        assert!(diff.spans.is_empty());
+        assert_eq!(diff.span_count, 5);
+
+        // A token missing on one side, as after a stop word filter
+        let mut diff = PyTokenizationDiff::default();
+        diff.add_text(0, text, &tokens_a, &tokens_a[1..]);
+        assert_eq!(spans(&diff), vec![("ab", 1, 0, 0)]);
+    }
+}
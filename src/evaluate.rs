@@ -0,0 +1,334 @@
+use std::collections::{HashMap, HashSet};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::schema::detail_index;
+use crate::token::PyToken;
+use crate::tokenizer::PyTokenizer;
+
+/// Differences between the gold tokens of a sentence and the tokens of the tokenizer.
+#[pyclass(name = "SentenceMismatch", module = "lindera")]
+#[derive(Clone)]
+pub struct PySentenceMismatch {
+    // Index of the sentence in the gold data
+    #[pyo3(get)]
+    pub index: usize,
+    #[pyo3(get)]
+    pub text: String,
+    // Gold tokens whose byte range the tokenizer did not produce
+    #[pyo3(get)]
+    pub missing: Vec<PyToken>,
+    // Tokens of the tokenizer whose byte range is not in the gold data
+    #[pyo3(get)]
+    pub extra: Vec<PyToken>,
+    // Gold and system tokens with the same byte range but a different value of the field
+    #[pyo3(get)]
+    pub field_mismatches: Vec<(PyToken, PyToken)>,
+}
+
+#[pymethods]
+impl PySentenceMismatch {
+    fn __repr__(&self) -> String {
+        format!(
+            "SentenceMismatch(index={}, text='{}', missing={}, extra={}, field_mismatches={})",
+            self.index,
+            self.text,
+            self.missing.len(),
+            self.extra.len(),
+            self.field_mismatches.len()
+        )
+    }
+}
+
+/// Scores of a tokenizer against gold data.
+#[pyclass(name = "Evaluation", module = "lindera")]
+#[derive(Clone, Default)]
+pub struct PyEvaluation {
+    #[pyo3(get)]
+    pub gold_count: usize,
+    #[pyo3(get)]
+    pub system_count: usize,
+    // Tokens whose byte range is both in the gold data and in the output of the tokenizer
+    #[pyo3(get)]
+    pub correct_count: usize,
+    #[pyo3(get)]
+    pub field: Option<String>,
+    // Correct tokens with a gold value of the field, and those of them whose value matches
+    #[pyo3(get)]
+    pub field_count: usize,
+    #[pyo3(get)]
+    pub field_correct_count: usize,
+    #[pyo3(get)]
+    pub mismatches: Vec<PySentenceMismatch>,
+}
+
+#[pymethods]
+impl PyEvaluation {
+    #[getter]
+    pub fn precision(&self) -> f64 {
+        ratio(self.correct_count, self.system_count)
+    }
+
+    #[getter]
+    pub fn recall(&self) -> f64 {
+        ratio(self.correct_count, self.gold_count)
+    }
+
+    #[getter]
+    pub fn f1(&self) -> f64 {
+        let (precision, recall) = (self.precision(), self.recall());
+        if precision + recall == 0.0 {
+            0.0
+        } else {
+            2.0 * precision * recall / (precision + recall)
+        }
+    }
+
+    /// Share of the correct tokens whose value of the field matches the gold data,
+    /// or `None` when no field was given or no correct token has a gold value.
+    #[getter]
+    pub fn field_accuracy(&self) -> Option<f64> {
+        (self.field.is_some() && self.field_count > 0)
+            .then(|| ratio(self.field_correct_count, self.field_count))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Evaluation(precision={:.4}, recall={:.4}, f1={:.4}, field_accuracy={}, mismatches={})",
+            self.precision(),
+            self.recall(),
+            self.f1(),
+            self.field_accuracy()
+                .map_or("None".to_string(), |accuracy| format!("{accuracy:.4}")),
+            self.mismatches.len()
+        )
+    }
+}
+
+impl PyEvaluation {
+    /// Compares the tokens of one sentence and adds them to the scores.
+    pub fn add_sentence(&mut self, index: usize, text: &str, gold: &[PyToken], system: &[PyToken]) {
+        // Whitespace tokens are not words, and gold data such as CoNLL-U has none
+        let system = system
+            .iter()
+            .filter(|token| !token.text.trim().is_empty())
+            .collect::<Vec<_>>();
+        let system_ranges = system
+            .iter()
+            .map(|token| ((token.byte_start, token.byte_end), *token))
+            .collect::<HashMap<_, _>>();
+        let gold_ranges = gold
+            .iter()
+            .map(|token| (token.byte_start, token.byte_end))
+            .collect::<HashSet<_>>();
+
+        let mut mismatch = PySentenceMismatch {
+            index,
+            text: text.to_string(),
+            missing: Vec::new(),
+            extra: Vec::new(),
+            field_mismatches: Vec::new(),
+        };
+        for gold_token in gold {
+            let range = (gold_token.byte_start, gold_token.byte_end);
+            let Some(system_token) = system_ranges.get(&range) else {
+                mismatch.missing.push(gold_token.clone());
+                continue;
+            };
+            self.correct_count += 1;
+
+            let Some(field) = &self.field else {
+                continue;
+            };
+            if let Some(expected) = gold_token.field_value(field) {
+                self.field_count += 1;
+                if system_token.field_value(field).as_ref() == Some(&expected) {
+                    self.field_correct_count += 1;
+                } else {
+                    mismatch
+                        .field_mismatches
+                        .push((gold_token.clone(), (*system_token).clone()));
+                }
+            }
+        }
+        mismatch.extra = system
+            .iter()
+            .filter(|token| !gold_ranges.contains(&(token.byte_start, token.byte_end)))
+            .map(|token| (*token).clone())
+            .collect();
+
+        self.gold_count += gold.len();
+        self.system_count += system.len();
+        if !mismatch.missing.is_empty()
+            || !mismatch.extra.is_empty()
+            || !mismatch.field_mismatches.is_empty()
+        {
+            self.mismatches.push(mismatch);
+        }
+    }
+}
+
+/// Rebuilds the text of a gold sentence by placing the surfaces at their byte offsets,
+/// with spaces in the gaps between them.
+pub fn gold_text(tokens: &[PyToken]) -> Result<String, String> {
+    let mut text = String::new();
+    for token in tokens {
+        if token.byte_start < text.len() {
+            return Err(format!(
+                "token '{}' at byte {} overlaps the previous token",
+                token.text, token.byte_start
+            ));
+        }
+        if token.byte_end.checked_sub(token.byte_start) != Some(token.text.len()) {
+            return Err(format!(
+                "token '{}' does not span bytes {}..{}",
+                token.text, token.byte_start, token.byte_end
+            ));
+        }
+        text.extend(std::iter::repeat_n(' ', token.byte_start - text.len()));
+        text.push_str(&token.text);
+    }
+
+    Ok(text)
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Tokenizes the sentences of gold data and scores the tokens against it.
+///
+/// `gold` is a list of sentences of `Token`, as read by `parse_mecab`, `parse_conllu` or `parse_wakati`.
+/// The text of each sentence is rebuilt from the gold tokens and tokenized with `tokenizer`.
+/// A token is correct when its byte range is also a gold token, which gives the word boundary
+/// precision, recall and F1. With `field`, such as `"part_of_speech"` or `"upos"`,
+/// the correct tokens are also compared on that field of the dictionary schema.
+/// The gold tokens then need a schema too, as `parse_mecab` gives them with `schema=`,
+/// and `ValueError` is raised otherwise.
+#[pyfunction]
+#[pyo3(signature = (tokenizer, gold, field=None))]
+pub fn evaluate(
+    py: Python<'_>,
+    tokenizer: PyRef<'_, PyTokenizer>,
+    gold: Vec<Vec<PyToken>>,
+    field: Option<String>,
+) -> PyResult<PyEvaluation> {
+    if let Some(field) = field.as_deref() {
+        if field != "upos" {
+            detail_index(&tokenizer.schema, field)
+                .map_err(|err| PyValueError::new_err(format!("Failed to evaluate: {err}")))?;
+        }
+        // Without a schema the fields of a gold token cannot be looked up
+        if let Some(index) = gold
+            .iter()
+            .position(|tokens| tokens.iter().any(|token| token.schema.is_none()))
+        {
+            return Err(PyValueError::new_err(format!(
+                "Failed to evaluate sentence {index}: gold tokens have no schema to look up field '{field}'"
+            )));
+        }
+    }
+
+    let tokenizer = &*tokenizer;
+    py.detach(|| {
+        let mut evaluation = PyEvaluation {
+            field,
+            ..Default::default()
+        };
+        for (index, tokens) in gold.iter().enumerate() {
+            let text = gold_text(tokens).map_err(|err| {
+                PyValueError::new_err(format!("Failed to evaluate sentence {index}: {err}"))
+            })?;
+            let system = tokenizer.tokenize_text(&text)?;
+            evaluation.add_sentence(index, &text, tokens, &system);
+        }
+
+        Ok(evaluation)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(surfaces: &[(&str, &str)]) -> Vec<PyToken> {
+        let schema = std::sync::Arc::new(lindera::dictionary::Schema::new(
+            [
+                "surface",
+                "left_context_id",
+                "right_context_id",
+                "cost",
+                "part_of_speech",
+            ]
+            .iter()
+            .map(|field| field.to_string())
+            .collect(),
+        ));
+        let mut offset = 0;
+        surfaces
+            .iter()
+            .map(|(surface, pos)| {
+                let mut token = PyToken::for_test(surface, offset, vec![pos.to_string()]);
+                token.schema = Some(schema.clone());
+                offset += surface.len();
+                token
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_add_sentence() {
+        let gold = tokens(&[("ab", "名詞"), ("c", "助詞"), ("d", "動詞")]);
+        let system = tokens(&[("ab", "名詞"), ("c", "名詞"), ("d", "名詞")]);
+        let mut evaluation = PyEvaluation {
+            field: Some("part_of_speech".to_string()),
+            ..Default::default()
+        };
+        evaluation.add_sentence(0, "abcd", &gold, &system);
+        assert_eq!(evaluation.correct_count, 3);
+        assert_eq!(evaluation.f1(), 1.0);
+        assert_eq!(evaluation.field_count, 3);
+        assert_eq!(evaluation.field_correct_count, 1);
+        assert_eq!(evaluation.mismatches[0].field_mismatches.len(), 2);
+
+        let system = tokens(&[("a", "名詞"), ("b", "名詞"), ("c", "助詞"), ("d", "動詞")]);
+        let mut evaluation = PyEvaluation::default();
+        evaluation.add_sentence(0, "abcd", &gold, &system);
+        assert_eq!(evaluation.correct_count, 2);
+        assert_eq!(evaluation.precision(), 0.5);
+        assert!((evaluation.recall() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(evaluation.field_accuracy(), None);
+        let mismatch = &evaluation.mismatches[0];
+        assert_eq!(mismatch.missing[0].text, "ab");
+        assert_eq!(
+            mismatch
+                .extra
+                .iter()
+                .map(|token| (token.byte_start, token.byte_end))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (1, 2)]
+        );
+
+        let mut evaluation = PyEvaluation::default();
+        evaluation.add_sentence(0, "abcd", &gold, &gold);
+        assert!(evaluation.mismatches.is_empty());
+    }
+
+    #[test]
+    fn test_gold_text() {
+        let mut gold = tokens(&[("東京", ""), ("に", "")]);
+        assert_eq!(gold_text(&gold).unwrap(), "東京に");
+
+        gold[1].byte_start += 1;
+        gold[1].byte_end += 1;
+        assert_eq!(gold_text(&gold).unwrap(), "東京 に");
+
+        gold[1].byte_start = 3;
+        assert!(gold_text(&gold).is_err());
+    }
+}
@@ -0,0 +1,536 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use pyo3::exceptions::{PyIndexError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString};
+use pyo3::{PyTraverseError, PyVisit};
+use serde_json::{Value, json};
+
+use lindera::character_filter::{
+    BoxCharacterFilter, CharacterFilterLoader, OffsetMapping, Transformation,
+};
+use lindera::dictionary::{Schema, WordId};
+use lindera::segmenter::Segmenter;
+use lindera::token::Token;
+use lindera::token_filter::{BoxTokenFilter, TokenFilterLoader};
+use lindera_dictionary::viterbi::WordEntry;
+
+use crate::config::ensure_keys;
+use crate::registry::FilterRegistry;
+use crate::token::{PyToken, TextIndex, WordEntries};
+use crate::util::{pydict_to_value, value_to_pydict};
+
+/// Filter of a tokenizer pipeline, either a built-in Lindera filter or a Python callable.
+///
+/// Every copy of a Python filter holds its own reference to the callable,
+/// so that each object holding one can report it to the garbage collector.
+pub enum FilterSpec {
+    Builtin { kind: String, args: Value },
+    Python(Py<PyAny>),
+}
+
+impl Clone for FilterSpec {
+    fn clone(&self) -> Self {
+        match self {
+            FilterSpec::Builtin { kind, args } => FilterSpec::Builtin {
+                kind: kind.clone(),
+                args: args.clone(),
+            },
+            FilterSpec::Python(func) => Python::attach(|py| FilterSpec::Python(func.clone_ref(py))),
+        }
+    }
+}
+
+impl FilterSpec {
+    // Create a filter spec from the `kind` and `args` given to the builder,
+    // where `kind` is either the name of a built-in filter in `registry` or a Python callable
+    pub fn extract(
+        kind: &Bound<'_, PyAny>,
+        args: Option<&Bound<'_, PyDict>>,
+        registry: &FilterRegistry,
+    ) -> PyResult<Self> {
+        if kind.is_instance_of::<PyString>() {
+            let kind: String = kind.extract()?;
+            let args = match args {
+                Some(dict) => pydict_to_value(dict)?,
+                None => json!({}),
+            };
+            registry
+                .validate(&kind, &args)
+                .map_err(|err| PyValueError::new_err(format!("Failed to append filter: {err}")))?;
+
+            Ok(FilterSpec::Builtin { kind, args })
+        } else if kind.is_callable() {
+            if args.is_some() {
+                return Err(PyValueError::new_err(
+                    "args cannot be given for a Python callable filter",
+                ));
+            }
+            Ok(FilterSpec::Python(kind.clone().unbind()))
+        } else {
+            Err(PyTypeError::new_err(format!(
+                "Filter must be a filter kind or a callable: {kind}"
+            )))
+        }
+    }
+
+    // Read an entry of the `character_filters` or `token_filters` section of a configuration
+    pub fn from_value(value: &Value) -> PyResult<Self> {
+        let kind = value["kind"].as_str().ok_or_else(|| {
+            PyValueError::new_err(format!("Failed to load filter: missing kind in {value}"))
+        })?;
+        let args = match value.get("args") {
+            None | Some(Value::Null) => json!({}),
+            Some(args) => args.clone(),
+        };
+
+        Ok(FilterSpec::Builtin {
+            kind: kind.to_string(),
+            args,
+        })
+    }
+
+    pub fn to_value(&self) -> PyResult<Value> {
+        match self {
+            FilterSpec::Builtin { kind, args } => Ok(json!({ "kind": kind, "args": args })),
+            FilterSpec::Python(_) => Err(PyValueError::new_err(
+                "A pipeline with Python callable filters cannot be exported as a configuration",
+            )),
+        }
+    }
+
+    // Convert to a `{"kind": ..., "args": ...}` dict, or the callable itself for a Python filter
+    pub fn to_object(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        match self {
+            FilterSpec::Builtin { .. } => value_to_pydict(py, &self.to_value()?),
+            FilterSpec::Python(func) => Ok(func.clone_ref(py)),
+        }
+    }
+
+    // Inverse of `to_object`
+    pub fn from_object(object: &Bound<'_, PyAny>, registry: &FilterRegistry) -> PyResult<Self> {
+        if let Ok(dict) = object.cast::<PyDict>() {
+            Self::from_value(&pydict_to_value(dict)?)
+        } else {
+            Self::extract(object, None, registry)
+        }
+    }
+
+    pub fn is_python(&self) -> bool {
+        matches!(self, FilterSpec::Python(_))
+    }
+
+    fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        match self {
+            FilterSpec::Builtin { .. } => Ok(()),
+            FilterSpec::Python(func) => visit.call(func),
+        }
+    }
+}
+
+/// Filters of a chain selected either by position or by kind.
+#[derive(FromPyObject)]
+pub enum FilterTarget {
+    Index(isize),
+    Kind(String),
+}
+
+impl FilterTarget {
+    // Positions of the selected filters in `filters`
+    fn positions(&self, filters: &[FilterSpec], registry: &FilterRegistry) -> PyResult<Vec<usize>> {
+        match self {
+            FilterTarget::Index(index) => {
+                let position = normalize_index(*index, filters.len()).ok_or_else(|| {
+                    PyIndexError::new_err(format!("{} index out of range: {index}", registry.kind))
+                })?;
+                Ok(vec![position])
+            }
+            FilterTarget::Kind(kind) => {
+                let positions: Vec<usize> = filters
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, filter)| {
+                        matches!(filter, FilterSpec::Builtin { kind: k, .. } if k == kind)
+                    })
+                    .map(|(position, _)| position)
+                    .collect();
+                if positions.is_empty() {
+                    return Err(PyValueError::new_err(format!(
+                        "No {} '{kind}' in the pipeline",
+                        registry.kind
+                    )));
+                }
+                Ok(positions)
+            }
+        }
+    }
+}
+
+// Insert a filter at `index`, counting from the end when negative, as `list.insert` does
+pub fn insert_filter(filters: &mut Vec<FilterSpec>, index: isize, filter: FilterSpec) {
+    let position = insert_position(index, filters.len());
+    filters.insert(position, filter);
+}
+
+// Remove the filter at an index, or every filter of a kind
+pub fn remove_filters(
+    filters: &mut Vec<FilterSpec>,
+    target: &FilterTarget,
+    registry: &FilterRegistry,
+) -> PyResult<()> {
+    let positions = target.positions(filters, registry)?;
+    for position in positions.into_iter().rev() {
+        filters.remove(position);
+    }
+
+    Ok(())
+}
+
+// Replace the args of the filter at an index, or of every filter of a kind
+pub fn set_filter_args(
+    filters: &mut [FilterSpec],
+    target: &FilterTarget,
+    args: &Bound<'_, PyDict>,
+    registry: &FilterRegistry,
+) -> PyResult<()> {
+    let new_args = pydict_to_value(args)?;
+    for position in target.positions(filters, registry)? {
+        match &mut filters[position] {
+            FilterSpec::Builtin { kind, args } => {
+                registry.validate(kind, &new_args).map_err(|err| {
+                    PyValueError::new_err(format!("Failed to set filter args: {err}"))
+                })?;
+                *args = new_args.clone();
+            }
+            FilterSpec::Python(_) => {
+                return Err(PyValueError::new_err(
+                    "args cannot be given for a Python callable filter",
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn normalize_index(index: isize, len: usize) -> Option<usize> {
+    let position = if index < 0 {
+        len.checked_sub(index.unsigned_abs())?
+    } else {
+        index as usize
+    };
+
+    (position < len).then_some(position)
+}
+
+fn insert_position(index: isize, len: usize) -> usize {
+    if index < 0 {
+        len.saturating_sub(index.unsigned_abs())
+    } else {
+        (index as usize).min(len)
+    }
+}
+
+/// Segmenter configuration and filter chains that a tokenizer is built from.
+#[derive(Clone)]
+pub struct PipelineSpec {
+    pub segmenter: Value,
+    pub character_filters: Vec<FilterSpec>,
+    pub token_filters: Vec<FilterSpec>,
+}
+
+impl PipelineSpec {
+    pub fn from_config(config: Value) -> PyResult<Self> {
+        let config = ensure_keys(config)
+            .map_err(|err| PyValueError::new_err(format!("Failed to load config: {err}")))?;
+
+        Ok(Self {
+            segmenter: config["segmenter"].clone(),
+            character_filters: filter_specs(&config["character_filters"])?,
+            token_filters: filter_specs(&config["token_filters"])?,
+        })
+    }
+
+    // Report the callables of the Python filters to the garbage collector
+    pub fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        for filter in self.character_filters.iter().chain(&self.token_filters) {
+            filter.traverse(visit)?;
+        }
+
+        Ok(())
+    }
+
+    // Drop the Python filters to break reference cycles through their callables
+    pub fn clear_python_filters(&mut self) {
+        self.character_filters.retain(|filter| !filter.is_python());
+        self.token_filters.retain(|filter| !filter.is_python());
+    }
+
+    // Assemble the configuration in the shape of lindera.yml
+    pub fn to_config(&self) -> PyResult<Value> {
+        Ok(json!({
+            "segmenter": self.segmenter,
+            "character_filters": self
+                .character_filters
+                .iter()
+                .map(FilterSpec::to_value)
+                .collect::<PyResult<Vec<_>>>()?,
+            "token_filters": self
+                .token_filters
+                .iter()
+                .map(FilterSpec::to_value)
+                .collect::<PyResult<Vec<_>>>()?,
+        }))
+    }
+}
+
+fn filter_specs(value: &Value) -> PyResult<Vec<FilterSpec>> {
+    match value {
+        Value::Null => Ok(Vec::new()),
+        Value::Array(filters) => filters.iter().map(FilterSpec::from_value).collect(),
+        _ => Err(PyValueError::new_err(format!(
+            "Failed to load filters: expected a list, got {value}"
+        ))),
+    }
+}
+
+/// Character filter loaded into a tokenizer.
+pub enum CharacterFilterStage {
+    Builtin(BoxCharacterFilter),
+    Python(Py<PyAny>),
+}
+
+impl CharacterFilterStage {
+    pub fn load(spec: &FilterSpec) -> PyResult<Self> {
+        match spec {
+            FilterSpec::Builtin { kind, args } => {
+                CharacterFilterLoader::load_from_value(kind, args)
+                    .map(CharacterFilterStage::Builtin)
+                    .map_err(|err| {
+                        PyValueError::new_err(format!(
+                            "Failed to load character filter '{kind}': {err}"
+                        ))
+                    })
+            }
+            FilterSpec::Python(func) => Ok(CharacterFilterStage::Python(Python::attach(|py| {
+                func.clone_ref(py)
+            }))),
+        }
+    }
+
+    pub fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        match self {
+            CharacterFilterStage::Builtin(_) => Ok(()),
+            CharacterFilterStage::Python(func) => visit.call(func),
+        }
+    }
+
+    pub fn is_python(&self) -> bool {
+        matches!(self, CharacterFilterStage::Python(_))
+    }
+
+    pub fn apply(&self, text: &mut String) -> PyResult<OffsetMapping> {
+        match self {
+            CharacterFilterStage::Builtin(filter) => filter
+                .apply(text)
+                .map_err(|err| PyValueError::new_err(format!("Failed to tokenize text: {err}"))),
+            CharacterFilterStage::Python(func) => {
+                let filtered: String =
+                    Python::attach(|py| func.call1(py, (text.as_str(),))?.extract(py))?;
+                let mapping = diff_mapping(text, &filtered);
+                *text = filtered;
+
+                Ok(mapping)
+            }
+        }
+    }
+}
+
+/// Token filter loaded into a tokenizer.
+pub enum TokenFilterStage {
+    Builtin(BoxTokenFilter),
+    Python(Py<PyAny>),
+}
+
+impl TokenFilterStage {
+    pub fn load(spec: &FilterSpec) -> PyResult<Self> {
+        match spec {
+            FilterSpec::Builtin { kind, args } => TokenFilterLoader::load_from_value(kind, args)
+                .map(TokenFilterStage::Builtin)
+                .map_err(|err| {
+                    PyValueError::new_err(format!("Failed to load token filter '{kind}': {err}"))
+                }),
+            FilterSpec::Python(func) => Ok(TokenFilterStage::Python(Python::attach(|py| {
+                func.clone_ref(py)
+            }))),
+        }
+    }
+
+    pub fn traverse(&self, visit: &PyVisit<'_>) -> Result<(), PyTraverseError> {
+        match self {
+            TokenFilterStage::Builtin(_) => Ok(()),
+            TokenFilterStage::Python(func) => visit.call(func),
+        }
+    }
+
+    pub fn is_python(&self) -> bool {
+        matches!(self, TokenFilterStage::Python(_))
+    }
+
+    // Apply the filter to the tokens of `text`, the output of the character filters
+    pub fn apply<'a>(
+        &self,
+        tokens: &mut Vec<Token<'a>>,
+        text: &str,
+        segmenter: &'a Segmenter,
+        schema: &Arc<Schema>,
+        entries: &mut WordEntries,
+    ) -> PyResult<()> {
+        match self {
+            TokenFilterStage::Builtin(filter) => filter
+                .apply(tokens)
+                .map_err(|err| PyValueError::new_err(format!("Failed to tokenize text: {err}"))),
+            TokenFilterStage::Python(func) => {
+                let index = TextIndex::new(text, false);
+                let input: Vec<PyToken> = tokens
+                    .iter_mut()
+                    .map(|token| {
+                        let entry = entries.get(token);
+                        PyToken::from_token(token, &index, schema, entry)
+                    })
+                    .collect();
+                let output: Vec<PyToken> =
+                    Python::attach(|py| func.call1(py, (input,))?.extract(py))?;
+
+                *tokens = output
+                    .into_iter()
+                    .map(|token| {
+                        if token.byte_start > token.byte_end
+                            || !text.is_char_boundary(token.byte_start)
+                            || !text.is_char_boundary(token.byte_end)
+                        {
+                            return Err(PyValueError::new_err(format!(
+                                "Token filter returned a token with invalid offsets: {}..{}",
+                                token.byte_start, token.byte_end
+                            )));
+                        }
+
+                        // The returned token decides its word id, and a token without one is unknown
+                        let word_id = match token.word_id {
+                            Some(id) if !token.is_unknown => WordId {
+                                id,
+                                is_system: !token.is_user_dictionary,
+                            },
+                            _ => WordId::default(),
+                        };
+                        if let (Some(word_cost), Some(left_id), Some(right_id)) = (
+                            token.word_cost,
+                            token.left_context_id,
+                            token.right_context_id,
+                        ) {
+                            entries.insert(
+                                token.byte_start,
+                                token.byte_end,
+                                WordEntry {
+                                    word_id,
+                                    word_cost,
+                                    left_id,
+                                    right_id,
+                                },
+                            );
+                        }
+                        let mut new_token = Token::new(
+                            Cow::Owned(token.text),
+                            token.byte_start,
+                            token.byte_end,
+                            token.position,
+                            word_id,
+                            &segmenter.dictionary,
+                            segmenter.user_dictionary.as_ref(),
+                        );
+                        new_token.position_length = token.position_length;
+                        new_token.details =
+                            Some(token.details.into_iter().map(Cow::Owned).collect());
+
+                        Ok(new_token)
+                    })
+                    .collect::<PyResult<_>>()?;
+
+                Ok(())
+            }
+        }
+    }
+}
+
+// Describe the change made by a Python character filter as a single transformation
+// covering everything between the common prefix and the common suffix of both texts
+fn diff_mapping(original: &str, filtered: &str) -> OffsetMapping {
+    let prefix: usize = original
+        .chars()
+        .zip(filtered.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    let suffix: usize = original[prefix..]
+        .chars()
+        .rev()
+        .zip(filtered[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+
+    if prefix == original.len() && prefix == filtered.len() {
+        return OffsetMapping::new();
+    }
+
+    OffsetMapping::with_transformations(vec![Transformation::new(
+        prefix,
+        original.len() - suffix,
+        prefix,
+        filtered.len() - suffix,
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_index() {
+        assert_eq!(normalize_index(0, 3), Some(0));
+        assert_eq!(normalize_index(2, 3), Some(2));
+        assert_eq!(normalize_index(3, 3), None);
+        assert_eq!(normalize_index(-1, 3), Some(2));
+        assert_eq!(normalize_index(-3, 3), Some(0));
+        assert_eq!(normalize_index(-4, 3), None);
+        assert_eq!(normalize_index(0, 0), None);
+    }
+
+    #[test]
+    fn test_insert_position() {
+        assert_eq!(insert_position(0, 3), 0);
+        assert_eq!(insert_position(5, 3), 3);
+        assert_eq!(insert_position(-1, 3), 2);
+        assert_eq!(insert_position(-5, 3), 0);
+    }
+
+    #[test]
+    fn test_diff_mapping() {
+        assert!(diff_mapping("東京都", "東京都").is_empty());
+
+        // "ABC-123" -> "ABC123"
+        let mapping = diff_mapping("品番ABC-123です", "品番ABC123です");
+        assert_eq!(
+            mapping.transformations,
+            vec![Transformation::new(9, 10, 9, 9)]
+        );
+
+        // "ＭＳ" -> "マイクロソフト"
+        let mapping = diff_mapping("ＭＳの製品", "マイクロソフトの製品");
+        assert_eq!(
+            mapping.transformations,
+            vec![Transformation::new(0, 6, 0, 21)]
+        );
+    }
+}
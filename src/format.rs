@@ -0,0 +1,392 @@
+use lindera::dictionary::Schema;
+
+use crate::schema::detail_index;
+use crate::sentence::PySentence;
+use crate::token::PyToken;
+
+/// Output format of MeCab, one `surface\tfeatures` line per token.
+pub const MECAB_NODE_FORMAT: &str = "%m\t%H\n";
+pub const MECAB_EOS_FORMAT: &str = "EOS\n";
+
+// Part-of-speech fields joined with `-` into the XPOS column of CoNLL-U, as in 名詞-固有名詞-地域
+const XPOS_FIELDS: &[&str] = &[
+    "part_of_speech_tag",
+    "part_of_speech",
+    "part_of_speech_subcategory_1",
+    "part_of_speech_subcategory_2",
+    "part_of_speech_subcategory_3",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Directive {
+    Literal(String),
+    // %m
+    Surface,
+    // %H
+    Features,
+    // %f[N], %f[name] or a comma separated list of them, as indices into the details
+    Feature(Vec<usize>),
+    // %ps
+    ByteStart,
+    // %pe
+    ByteEnd,
+}
+
+/// Output template in the style of the `-F` option of MeCab.
+///
+/// `%m` is the surface, `%H` the comma separated features, `%f[N]` the Nth feature
+/// and `%f[name]` the feature named `name` in the dictionary schema.
+/// `%f[6,7]` joins several features with commas, `%ps` and `%pe` are the byte offsets of the token
+/// and `%%` is a literal `%`. The escapes `\t`, `\n` and `\\` are expanded as in MeCab.
+/// Features that contain a comma or `"` are quoted with `"`, doubling the quotes inside them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    directives: Vec<Directive>,
+}
+
+impl Template {
+    pub fn parse(template: &str, schema: &Schema) -> Result<Self, String> {
+        let mut directives = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some('\\') => literal.push('\\'),
+                    Some(c) => {
+                        literal.push('\\');
+                        literal.push(c);
+                    }
+                    None => literal.push('\\'),
+                },
+                '%' => {
+                    let directive = match chars.next() {
+                        Some('%') => {
+                            literal.push('%');
+                            continue;
+                        }
+                        Some('m') => Directive::Surface,
+                        Some('H') => Directive::Features,
+                        Some('p') => match chars.next() {
+                            Some('s') => Directive::ByteStart,
+                            Some('e') => Directive::ByteEnd,
+                            other => {
+                                return Err(format!(
+                                    "unsupported directive '%p{}'",
+                                    other.map(String::from).unwrap_or_default()
+                                ));
+                            }
+                        },
+                        Some('f') => {
+                            if chars.next() != Some('[') {
+                                return Err("'%f' must be followed by '[field]'".to_string());
+                            }
+                            let mut fields = String::new();
+                            loop {
+                                match chars.next() {
+                                    Some(']') => break,
+                                    Some(c) => fields.push(c),
+                                    None => return Err(format!("unclosed '%f[{fields}'")),
+                                }
+                            }
+                            Directive::Feature(
+                                fields
+                                    .split(',')
+                                    .map(|field| detail_index(schema, field.trim()))
+                                    .collect::<Result<_, _>>()?,
+                            )
+                        }
+                        Some(c) => return Err(format!("unsupported directive '%{c}'")),
+                        None => return Err("template ends with '%'".to_string()),
+                    };
+                    if !literal.is_empty() {
+                        directives.push(Directive::Literal(std::mem::take(&mut literal)));
+                    }
+                    directives.push(directive);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            directives.push(Directive::Literal(literal));
+        }
+
+        Ok(Template { directives })
+    }
+
+    /// Appends the template expanded for `token` to `output`.
+    pub fn render(&self, token: &PyToken, output: &mut String) {
+        for directive in &self.directives {
+            match directive {
+                Directive::Literal(literal) => output.push_str(literal),
+                Directive::Surface => output.push_str(&token.text),
+                Directive::Features => {
+                    for (i, detail) in token.details.iter().enumerate() {
+                        if i > 0 {
+                            output.push(',');
+                        }
+                        push_feature(output, detail);
+                    }
+                }
+                Directive::Feature(indices) => {
+                    for (i, index) in indices.iter().enumerate() {
+                        if i > 0 {
+                            output.push(',');
+                        }
+                        // Unknown words may have fewer features than the schema
+                        push_feature(
+                            output,
+                            token.details.get(*index).map_or("*", String::as_str),
+                        );
+                    }
+                }
+                Directive::ByteStart => output.push_str(&token.byte_start.to_string()),
+                Directive::ByteEnd => output.push_str(&token.byte_end.to_string()),
+            }
+        }
+    }
+}
+
+// Append a feature, quoted with `"` as in a CSV dictionary when it contains a comma or a quote
+fn push_feature(output: &mut String, feature: &str) {
+    if feature.contains([',', '"']) {
+        output.push('"');
+        output.push_str(&feature.replace('"', "\"\""));
+        output.push('"');
+    } else {
+        output.push_str(feature);
+    }
+}
+
+/// Formats tokens with `node_format` followed by `eos_format`.
+pub fn format_tokens(tokens: &[PyToken], node_format: &Template, eos_format: &str) -> String {
+    let mut output = String::new();
+    for token in tokens {
+        node_format.render(token, &mut output);
+    }
+    output.push_str(eos_format);
+
+    output
+}
+
+/// Joins the surfaces of tokens with spaces, as the wakati output of MeCab.
+pub fn format_wakati(tokens: &[PyToken]) -> String {
+    let mut output = tokens
+        .iter()
+        .map(|token| token.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    output.push('\n');
+
+    output
+}
+
+/// Formats tokenized sentences as CoNLL-U, with the dependency columns left empty.
+///
+/// LEMMA is the base form, falling back to the surface, UPOS is mapped from the part of speech
+/// and XPOS joins the part-of-speech fields of the schema. MISC holds `SpaceAfter=No` when the token
+/// is directly followed by another character of `text`, and the character range of the token, when known, as `TokenRange`.
+/// Tokens that consist of whitespace only are left out, as CoNLL-U has no room for them.
+pub fn format_conllu(text: &str, sentences: &[(PySentence, Vec<PyToken>)]) -> String {
+    let mut output = String::new();
+    for (i, (sentence, tokens)) in sentences.iter().enumerate() {
+        output.push_str(&format!("# sent_id = {}\n", i + 1));
+        output.push_str(&format!("# text = {}\n", conllu_comment(&sentence.text)));
+
+        let tokens = tokens.iter().filter(|token| !token.text.trim().is_empty());
+        for (id, token) in tokens.enumerate() {
+            let lemma = token
+                .base_form()
+                .filter(|lemma| lemma != "*")
+                .unwrap_or_else(|| token.text.clone());
+            let space_after = text
+                .get(token.byte_end..)
+                .and_then(|rest| rest.chars().next())
+                .is_none_or(char::is_whitespace);
+            let mut misc = Vec::new();
+            if !space_after {
+                misc.push("SpaceAfter=No".to_string());
+            }
+            if let (Some(char_start), Some(char_end)) = (token.char_start, token.char_end) {
+                misc.push(format!("TokenRange={char_start}:{char_end}"));
+            }
+
+            let columns = [
+                (id + 1).to_string(),
+                conllu_field(&token.text),
+                conllu_field(&lemma),
+                token.upos().unwrap_or_else(|| "X".to_string()),
+                conllu_field(&xpos(token)),
+                "_".to_string(),
+                "_".to_string(),
+                "_".to_string(),
+                "_".to_string(),
+                conllu_field(&misc.join("|")),
+            ];
+            output.push_str(&columns.join("\t"));
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+fn xpos(token: &PyToken) -> String {
+    let Some(schema) = &token.schema else {
+        return String::new();
+    };
+    XPOS_FIELDS
+        .iter()
+        .filter_map(|field| detail_index(schema, field).ok())
+        .filter_map(|index| token.details.get(index))
+        .filter(|value| value.as_str() != "*")
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+// Fields are separated by tabs and records by line breaks, an empty field is written as `_`
+fn conllu_field(value: &str) -> String {
+    if value.is_empty() {
+        "_".to_string()
+    } else {
+        value.replace(['\t', '\n', '\r'], " ")
+    }
+}
+
+fn conllu_comment(value: &str) -> String {
+    value.replace(['\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn schema() -> Schema {
+        Schema::new(
+            [
+                "surface",
+                "left_context_id",
+                "right_context_id",
+                "cost",
+                "part_of_speech",
+                "part_of_speech_subcategory_1",
+                "base_form",
+                "reading",
+            ]
+            .iter()
+            .map(|field| field.to_string())
+            .collect(),
+        )
+    }
+
+    fn token(text: &str, details: &[&str]) -> PyToken {
+        PyToken::for_test(
+            text,
+            0,
+            details.iter().map(|detail| detail.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_template() {
+        let tokens = vec![
+            token("東京", &["名詞", "固有名詞", "東京", "トウキョウ"]),
+            token("ｘ", &["名詞", "一般"]),
+        ];
+
+        let template = Template::parse(MECAB_NODE_FORMAT, &schema()).unwrap();
+        assert_eq!(
+            format_tokens(&tokens, &template, MECAB_EOS_FORMAT),
+            "東京\t名詞,固有名詞,東京,トウキョウ\nｘ\t名詞,一般\nEOS\n"
+        );
+
+        let template = Template::parse(
+            "%m\\t%f[0]/%f[reading]/%f[base_form,3] %ps-%pe 100%%\\n",
+            &schema(),
+        )
+        .unwrap();
+        assert_eq!(
+            format_tokens(&tokens, &template, ""),
+            "東京\t名詞/トウキョウ/東京,トウキョウ 0-6 100%\nｘ\t名詞/*/*,* 0-3 100%\n"
+        );
+
+        // Features with commas or quotes are quoted as in a CSV dictionary
+        let tokens = vec![token("a,b", &["記号", "a,b", "\"", "*"])];
+        let template = Template::parse(MECAB_NODE_FORMAT, &schema()).unwrap();
+        assert_eq!(
+            format_tokens(&tokens, &template, MECAB_EOS_FORMAT),
+            "a,b\t記号,\"a,b\",\"\"\"\",*\nEOS\n"
+        );
+        let template = Template::parse("%f[1]\\n", &schema()).unwrap();
+        assert_eq!(format_tokens(&tokens, &template, ""), "\"a,b\"\n");
+
+        assert!(
+            Template::parse("%f[pos]", &schema())
+                .unwrap_err()
+                .contains("unknown field 'pos'")
+        );
+        assert!(Template::parse("%f[cost]", &schema()).is_err());
+        assert!(Template::parse("%f[0", &schema()).is_err());
+        assert!(Template::parse("%x", &schema()).is_err());
+    }
+
+    #[test]
+    fn test_format_conllu() {
+        let text = "東京 に🌧。";
+        let mut tokens = vec![
+            token("東京", &["名詞", "固有名詞", "東京", "トウキョウ"]),
+            token(" ", &["記号", "空白"]),
+            token("に", &["助詞", "格助詞", "に", "ニ"]),
+            token("🌧", &["UNK"]),
+            token("。", &["記号", "句点", "。", "。"]),
+        ];
+        let mut offset = 0;
+        for token in &mut tokens {
+            token.byte_start = offset;
+            token.byte_end = offset + token.text.len();
+            let char_start = text[..offset].chars().count();
+            token.char_start = Some(char_start);
+            token.char_end = Some(char_start + token.text.chars().count());
+            token.schema = Some(Arc::new(schema()));
+            offset = token.byte_end;
+        }
+        let sentence = PySentence::new(text.to_string(), 0, text.len(), 0, 6, None, None);
+
+        assert_eq!(
+            format_conllu(text, &[(sentence, tokens)]),
+            "# sent_id = 1\n\
+             # text = 東京 に🌧。\n\
+             1\t東京\t東京\tPROPN\t名詞-固有名詞\t_\t_\t_\t_\tTokenRange=0:2\n\
+             2\tに\tに\tADP\t助詞-格助詞\t_\t_\t_\t_\tSpaceAfter=No|TokenRange=3:4\n\
+             3\t🌧\t🌧\tX\tUNK\t_\t_\t_\t_\tSpaceAfter=No|TokenRange=4:5\n\
+             4\t。\t。\tPUNCT\t記号-句点\t_\t_\t_\t_\tTokenRange=5:6\n\
+             \n"
+        );
+
+        // A token followed by a space and without character offsets has an empty MISC column
+        let text = "東京 ";
+        let mut tokens = vec![token("東京", &["名詞", "固有名詞", "東京", "トウキョウ"])];
+        tokens[0].schema = Some(Arc::new(schema()));
+        let sentence = PySentence::new(text.to_string(), 0, text.len(), 0, 3, None, None);
+        assert_eq!(
+            format_conllu(text, &[(sentence, tokens)]),
+            "# sent_id = 1\n\
+             # text = 東京 \n\
+             1\t東京\t東京\tPROPN\t名詞-固有名詞\t_\t_\t_\t_\t_\n\
+             \n"
+        );
+    }
+
+    #[test]
+    fn test_format_wakati() {
+        let tokens = vec![token("東京", &[]), token("へ", &[])];
+        assert_eq!(format_wakati(&tokens), "東京 へ\n");
+    }
+}
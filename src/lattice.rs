@@ -0,0 +1,754 @@
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use pyo3::prelude::*;
+
+use lindera::dictionary::{Dictionary, UserDictionary};
+use lindera::mode::Mode;
+use lindera::segmenter::Segmenter;
+use lindera::token::Token;
+use lindera_dictionary::viterbi::{Edge, EdgeId, Lattice as ViterbiLattice, WordEntry};
+
+use crate::token::WordEntries;
+
+const BOS_NODE: usize = 0;
+const EOS_NODE: usize = 1;
+
+// Upper bound on the number of partial paths expanded per sentence when searching for N-best paths.
+const MAX_NBEST_EXPANSIONS: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Bos,
+    Eos,
+    Known,
+    Unknown,
+    User,
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub word_entry: WordEntry,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub kanji_only: bool,
+    pub path_cost: i32,
+    pub left_node: Option<usize>,
+}
+
+impl Node {
+    fn new(kind: NodeKind, word_entry: WordEntry, byte_start: usize, byte_end: usize) -> Self {
+        Node {
+            kind,
+            word_entry,
+            byte_start,
+            byte_end,
+            kanji_only: false,
+            path_cost: i32::MAX,
+            left_node: None,
+        }
+    }
+
+    // Build a Lindera edge so that the penalty of the segmentation mode can be evaluated
+    fn as_edge(&self) -> Edge {
+        Edge {
+            word_entry: self.word_entry,
+            start_index: self.byte_start as u32,
+            stop_index: self.byte_end as u32,
+            kanji_only: self.kanji_only,
+            ..Edge::default()
+        }
+    }
+}
+
+/// Word lattice of a single sentence.
+///
+/// This mirrors the lattice that Lindera builds internally in `Segmenter::segment`,
+/// `Lattice::set_text` and `Lattice::calculate_path_costs` in `src/viterbi.rs` of
+/// lindera-dictionary 1.1.2, the version pinned in Cargo.toml, but keeps every candidate
+/// node so that the lattice can be inspected and searched for paths other than the best one.
+pub struct Lattice {
+    pub nodes: Vec<Node>,
+    starts_at: Vec<Vec<usize>>,
+    ends_at: Vec<Vec<usize>>,
+}
+
+impl Lattice {
+    pub fn build(
+        text: &str,
+        dictionary: &Dictionary,
+        user_dictionary: Option<&UserDictionary>,
+        mode: &Mode,
+    ) -> Self {
+        let len = text.len();
+        let mut lattice = Lattice {
+            nodes: Vec::new(),
+            starts_at: vec![Vec::new(); len + 1],
+            ends_at: vec![Vec::new(); len + 1],
+        };
+
+        let mut bos = Node::new(NodeKind::Bos, WordEntry::default(), 0, 0);
+        bos.path_cost = 0;
+        lattice.nodes.push(bos);
+        lattice
+            .nodes
+            .push(Node::new(NodeKind::Eos, WordEntry::default(), len, len));
+        lattice.ends_at[0].push(BOS_NODE);
+        lattice.starts_at[len].push(EOS_NODE);
+
+        let char_definitions = &dictionary.character_definition;
+        let unknown_dictionary = &dictionary.unknown_dictionary;
+
+        // index of the last character of unknown word
+        let mut unknown_word_end: Option<usize> = None;
+
+        for start in 0..len {
+            // No node is ending here, so no word can start here.
+            if lattice.ends_at[start].is_empty() {
+                continue;
+            }
+
+            let suffix = &text[start..];
+            let mut found = false;
+
+            // lookup user dictionary
+            if let Some(user_dictionary) = user_dictionary {
+                for (prefix_len, word_entry) in user_dictionary.dict.prefix(suffix) {
+                    lattice.add_node(
+                        NodeKind::User,
+                        word_entry,
+                        start,
+                        start + prefix_len,
+                        &suffix[..prefix_len],
+                    );
+                    found = true;
+                }
+            }
+
+            // lookup system dictionary
+            for (prefix_len, word_entry) in dictionary.prefix_dictionary.prefix(suffix) {
+                lattice.add_node(
+                    NodeKind::Known,
+                    word_entry,
+                    start,
+                    start + prefix_len,
+                    &suffix[..prefix_len],
+                );
+                found = true;
+            }
+
+            // In the case of normal mode, it doesn't process unknown word greedily.
+            if mode.is_search() || unknown_word_end.map(|index| index <= start).unwrap_or(true) {
+                let Some(first_char) = suffix.chars().next() else {
+                    continue;
+                };
+
+                for (category_ord, &category) in char_definitions
+                    .lookup_categories(first_char)
+                    .iter()
+                    .enumerate()
+                {
+                    let category_data = char_definitions.lookup_definition(category);
+                    if !category_data.invoke && found {
+                        continue;
+                    }
+
+                    let mut num_chars = 1;
+                    if category_data.group {
+                        for c in suffix.chars().skip(1) {
+                            let categories = char_definitions.lookup_categories(c);
+                            if categories.len() > category_ord
+                                && categories[category_ord] == category
+                            {
+                                num_chars += 1;
+                            } else {
+                                break;
+                            }
+                        }
+                    }
+
+                    let byte_len = suffix
+                        .char_indices()
+                        .nth(num_chars)
+                        .map_or(suffix.len(), |(pos, _)| pos);
+                    for &word_id in unknown_dictionary.lookup_word_ids(category) {
+                        lattice.add_node(
+                            NodeKind::Unknown,
+                            unknown_dictionary.word_entry(word_id),
+                            start,
+                            start + byte_len,
+                            &suffix[..byte_len],
+                        );
+                    }
+                    unknown_word_end = Some(start + byte_len);
+                }
+            }
+        }
+
+        lattice.calculate_path_costs(dictionary, mode);
+
+        lattice
+    }
+
+    fn add_node(
+        &mut self,
+        kind: NodeKind,
+        word_entry: WordEntry,
+        byte_start: usize,
+        byte_end: usize,
+        surface: &str,
+    ) {
+        let mut node = Node::new(kind, word_entry, byte_start, byte_end);
+        node.kanji_only = is_kanji_only(surface);
+
+        let node_id = self.nodes.len();
+        self.nodes.push(node);
+        self.starts_at[byte_start].push(node_id);
+        self.ends_at[byte_end].push(node_id);
+    }
+
+    // Cost of connecting `left` to `right`, including the word cost of `right`
+    fn connection_cost(
+        &self,
+        dictionary: &Dictionary,
+        mode: &Mode,
+        left: usize,
+        right: usize,
+    ) -> i32 {
+        let left_node = &self.nodes[left];
+        let right_node = &self.nodes[right];
+
+        dictionary.connection_cost_matrix.cost(
+            left_node.word_entry.right_id(),
+            right_node.word_entry.left_id(),
+        ) + mode.penalty_cost(&left_node.as_edge())
+            + right_node.word_entry.word_cost as i32
+    }
+
+    fn calculate_path_costs(&mut self, dictionary: &Dictionary, mode: &Mode) {
+        for i in 0..self.starts_at.len() {
+            for right_idx in 0..self.starts_at[i].len() {
+                let right = self.starts_at[i][right_idx];
+
+                let mut best_cost = i32::MAX;
+                let mut best_left = None;
+                for &left in &self.ends_at[i] {
+                    if self.nodes[left].path_cost == i32::MAX {
+                        continue;
+                    }
+                    let path_cost = self.nodes[left].path_cost
+                        + self.connection_cost(dictionary, mode, left, right);
+                    if path_cost < best_cost {
+                        best_cost = path_cost;
+                        best_left = Some(left);
+                    }
+                }
+
+                if let Some(left) = best_left {
+                    let node = &mut self.nodes[right];
+                    node.left_node = Some(left);
+                    node.path_cost = best_cost;
+                }
+            }
+        }
+    }
+
+    pub fn node(&self, node_id: usize) -> &Node {
+        &self.nodes[node_id]
+    }
+
+    /// Returns the ids of the nodes that can precede the given node.
+    pub fn left_nodes(&self, node_id: usize) -> &[usize] {
+        if node_id == BOS_NODE {
+            return &[];
+        }
+        &self.ends_at[self.nodes[node_id].byte_start]
+    }
+
+    /// Returns the total cost of the best path.
+    pub fn best_cost(&self) -> i32 {
+        self.nodes[EOS_NODE].path_cost
+    }
+
+    /// Returns the node ids of the best path, excluding BOS and EOS.
+    pub fn best_path(&self) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut node_id = self.nodes[EOS_NODE].left_node;
+        while let Some(id) = node_id {
+            if id == BOS_NODE {
+                break;
+            }
+            path.push(id);
+            node_id = self.nodes[id].left_node;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Returns up to `n` paths in ascending order of total cost.
+    ///
+    /// Each path is a list of node ids excluding BOS and EOS, paired with its total cost.
+    /// The search runs backward from EOS, using the forward Viterbi costs as an exact heuristic.
+    pub fn nbest_paths(
+        &self,
+        dictionary: &Dictionary,
+        mode: &Mode,
+        n: usize,
+    ) -> Vec<(Vec<usize>, i32)> {
+        // Partial paths are stored as linked lists running from a node towards EOS
+        let mut arena: Vec<(usize, Option<usize>)> = vec![(EOS_NODE, None)];
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((self.best_cost() as i64, 0_i64, 0_usize)));
+
+        let mut paths = Vec::new();
+        let mut expansions = 0;
+        while let Some(Reverse((_, cost, entry))) = queue.pop() {
+            let (node_id, _) = arena[entry];
+
+            if node_id == BOS_NODE {
+                let mut path = Vec::new();
+                let mut next = arena[entry].1;
+                while let Some(idx) = next {
+                    let (id, parent) = arena[idx];
+                    if id != EOS_NODE {
+                        path.push(id);
+                    }
+                    next = parent;
+                }
+                paths.push((path, cost as i32));
+                if paths.len() >= n {
+                    break;
+                }
+                continue;
+            }
+
+            expansions += 1;
+            if expansions > MAX_NBEST_EXPANSIONS {
+                break;
+            }
+
+            for &left in &self.ends_at[self.nodes[node_id].byte_start] {
+                let left_cost = self.nodes[left].path_cost;
+                if left_cost == i32::MAX {
+                    continue;
+                }
+                let cost = cost + self.connection_cost(dictionary, mode, left, node_id) as i64;
+                arena.push((left, Some(entry)));
+                queue.push(Reverse((left_cost as i64 + cost, cost, arena.len() - 1)));
+            }
+        }
+
+        paths
+    }
+}
+
+#[inline]
+fn is_kanji(c: char) -> bool {
+    (19968..=40879).contains(&(c as u32))
+}
+
+#[inline]
+fn is_kanji_only(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(is_kanji)
+}
+
+/// Splits text into the byte ranges that Lindera segments independently.
+pub fn sentence_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut sentence_start = 0;
+    for (index, ch) in text.char_indices() {
+        if matches!(ch, '\n' | '\t' | '。' | '、') {
+            let sentence_end = index + ch.len_utf8();
+            ranges.push((sentence_start, sentence_end));
+            sentence_start = sentence_end;
+        }
+    }
+    if sentence_start < text.len() {
+        ranges.push((sentence_start, text.len()));
+    }
+    ranges
+}
+
+/// Segments text exactly as `Segmenter::segment` in `src/segmenter.rs` of lindera 1.1.2 does,
+/// and also returns the dictionary entry of every token, taken from the best path of the lattice.
+pub fn segment_with_entries<'a>(
+    segmenter: &'a Segmenter,
+    text: &'a str,
+) -> (Vec<Token<'a>>, WordEntries) {
+    let dictionary = &segmenter.dictionary;
+    let user_dictionary = segmenter.user_dictionary.as_ref();
+    let mut lattice = ViterbiLattice::default();
+    let mut tokens = Vec::new();
+    let mut entries = WordEntries::default();
+
+    for (sentence_start, sentence_end) in sentence_ranges(text) {
+        lattice.set_text(
+            &dictionary.prefix_dictionary,
+            &user_dictionary.map(|d| &d.dict),
+            &dictionary.character_definition,
+            &dictionary.unknown_dictionary,
+            &text[sentence_start..sentence_end],
+            &segmenter.mode,
+        );
+        lattice.calculate_path_costs(&dictionary.connection_cost_matrix, &segmenter.mode);
+
+        // Walk back from EOS, which has no word, to the edge right after BOS
+        let mut path = Vec::new();
+        let mut edge = lattice.edge(EdgeId(EOS_NODE as u32));
+        while let Some(left_edge) = edge.left_edge {
+            edge = lattice.edge(left_edge);
+            if edge.left_edge.is_some() {
+                path.push(edge);
+            }
+        }
+
+        for edge in path.into_iter().rev() {
+            let byte_start = sentence_start + edge.start_index as usize;
+            let byte_end = sentence_start + edge.stop_index as usize;
+            let position = tokens.len();
+            tokens.push(Token::new(
+                Cow::Borrowed(&text[byte_start..byte_end]),
+                byte_start,
+                byte_end,
+                position,
+                edge.word_entry.word_id,
+                dictionary,
+                user_dictionary,
+            ));
+            entries.insert(byte_start, byte_end, edge.word_entry);
+        }
+    }
+
+    (tokens, entries)
+}
+
+/// Segments text and returns up to `n` segmentations in ascending order of total cost.
+///
+/// Each sentence is searched independently, as in `Segmenter::segment`, and the per-sentence
+/// results are combined so that the costs of the returned segmentations are the lowest overall.
+pub fn segment_nbest<'a>(
+    segmenter: &'a Segmenter,
+    text: &'a str,
+    n: usize,
+) -> Vec<(Vec<Token<'a>>, WordEntries, i32)> {
+    let dictionary = &segmenter.dictionary;
+    let user_dictionary = segmenter.user_dictionary.as_ref();
+
+    // Combination of the chosen path of every sentence processed so far, with its total cost
+    let mut combinations: Vec<(Vec<usize>, i64)> = vec![(Vec::new(), 0)];
+    let mut sentences = Vec::new();
+
+    for (sentence_start, sentence_end) in sentence_ranges(text) {
+        let lattice = Lattice::build(
+            &text[sentence_start..sentence_end],
+            dictionary,
+            user_dictionary,
+            &segmenter.mode,
+        );
+        let paths = lattice.nbest_paths(dictionary, &segmenter.mode, n);
+
+        let mut merged: Vec<(Vec<usize>, i64)> =
+            Vec::with_capacity(combinations.len() * paths.len());
+        for (choices, total_cost) in &combinations {
+            for (path_idx, (_, cost)) in paths.iter().enumerate() {
+                let mut choices = choices.clone();
+                choices.push(path_idx);
+                merged.push((choices, total_cost + *cost as i64));
+            }
+        }
+        merged.sort_by_key(|(_, cost)| *cost);
+        merged.truncate(n);
+        combinations = merged;
+
+        sentences.push((sentence_start, lattice, paths));
+    }
+
+    combinations
+        .into_iter()
+        .map(|(choices, total_cost)| {
+            let mut tokens = Vec::new();
+            let mut entries = WordEntries::default();
+            for ((sentence_start, lattice, paths), path_idx) in sentences.iter().zip(choices) {
+                for &node_id in &paths[path_idx].0 {
+                    let node = lattice.node(node_id);
+                    let byte_start = sentence_start + node.byte_start;
+                    let byte_end = sentence_start + node.byte_end;
+                    let position = tokens.len();
+                    tokens.push(Token::new(
+                        Cow::Borrowed(&text[byte_start..byte_end]),
+                        byte_start,
+                        byte_end,
+                        position,
+                        node.word_entry.word_id,
+                        dictionary,
+                        user_dictionary,
+                    ));
+                    entries.insert(byte_start, byte_end, node.word_entry);
+                }
+            }
+            (tokens, entries, total_cost as i32)
+        })
+        .collect()
+}
+
+#[pyclass(name = "LatticeNode")]
+#[derive(Clone)]
+pub struct PyLatticeNode {
+    #[pyo3(get)]
+    pub id: usize,
+    #[pyo3(get)]
+    pub kind: String,
+    #[pyo3(get)]
+    pub surface: String,
+    #[pyo3(get)]
+    pub byte_start: usize,
+    #[pyo3(get)]
+    pub byte_end: usize,
+    #[pyo3(get)]
+    pub sentence: usize,
+    #[pyo3(get)]
+    pub word_id: Option<u32>,
+    #[pyo3(get)]
+    pub word_cost: i16,
+    #[pyo3(get)]
+    pub left_context_id: u16,
+    #[pyo3(get)]
+    pub right_context_id: u16,
+    #[pyo3(get)]
+    pub cost: Option<i32>,
+    #[pyo3(get)]
+    pub left_node: Option<usize>,
+    #[pyo3(get)]
+    pub details: Vec<String>,
+}
+
+#[pymethods]
+impl PyLatticeNode {
+    fn __str__(&self) -> String {
+        format!("LatticeNode(id={}, surface='{}')", self.id, self.surface)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "LatticeNode(id={}, kind='{}', surface='{}', byte_start={}, byte_end={}, word_cost={}, cost={:?})",
+            self.id,
+            self.kind,
+            self.surface,
+            self.byte_start,
+            self.byte_end,
+            self.word_cost,
+            self.cost
+        )
+    }
+}
+
+/// Word lattice built by a `Segmenter` for a text.
+///
+/// The text is split into sentences the same way `Segmenter.segment` does, and each
+/// sentence has its own BOS and EOS nodes. Accumulated costs are relative to the
+/// BOS node of the sentence.
+#[pyclass(name = "Lattice")]
+pub struct PyLattice {
+    #[pyo3(get)]
+    pub text: String,
+    #[pyo3(get)]
+    pub nodes: Vec<PyLatticeNode>,
+    #[pyo3(get)]
+    pub best_path: Vec<usize>,
+    // Every possible connection between two nodes, as (left node id, right node id)
+    connections: Vec<(usize, usize)>,
+}
+
+#[pymethods]
+impl PyLattice {
+    /// Returns the edges of the best path as (left node id, right node id) pairs.
+    #[getter]
+    fn best_path_edges(&self) -> Vec<(usize, usize)> {
+        self.best_path_edge_set()
+    }
+
+    /// Returns every possible connection between two nodes as (left node id, right node id) pairs.
+    #[getter]
+    fn edges(&self) -> Vec<(usize, usize)> {
+        self.connections.clone()
+    }
+
+    /// Renders the lattice in the Graphviz DOT language, highlighting the best path.
+    fn to_dot(&self) -> String {
+        let best_path_edges = self.best_path_edge_set();
+
+        let mut dot = String::from("digraph lattice {\n");
+        dot.push_str("  rankdir=LR;\n");
+        dot.push_str("  node [shape=box, fontname=\"sans-serif\"];\n");
+
+        for node in &self.nodes {
+            let label = match node.kind.as_str() {
+                "BOS" | "EOS" => node.kind.clone(),
+                _ => [
+                    escape_dot(&node.surface),
+                    escape_dot(&node.details.join(",")),
+                    format!(
+                        "word_cost={} cost={}",
+                        node.word_cost,
+                        node.cost.map_or("-".to_string(), |cost| cost.to_string())
+                    ),
+                ]
+                .join("\\n"),
+            };
+            let style = if self.best_path.contains(&node.id) {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            };
+            dot.push_str(&format!("  n{} [label=\"{}\"{}];\n", node.id, label, style));
+        }
+
+        for &(left, right) in &self.connections {
+            let style = if best_path_edges.contains(&(left, right)) {
+                "color=red, penwidth=2"
+            } else {
+                "color=gray"
+            };
+            dot.push_str(&format!("  n{left} -> n{right} [{style}];\n"));
+        }
+
+        dot.push('}');
+        dot.push('\n');
+        dot
+    }
+
+    fn __len__(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn __str__(&self) -> String {
+        format!("Lattice(nodes={})", self.nodes.len())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Lattice(text='{}', nodes={}, best_path={:?})",
+            self.text,
+            self.nodes.len(),
+            self.best_path
+        )
+    }
+}
+
+impl PyLattice {
+    // Internal helper function to build the lattices of every sentence of a text
+    pub fn build(segmenter: &Segmenter, text: &str) -> Self {
+        let dictionary = &segmenter.dictionary;
+        let user_dictionary = segmenter.user_dictionary.as_ref();
+
+        let mut nodes = Vec::new();
+        let mut best_path = Vec::new();
+        let mut connections = Vec::new();
+        let mut previous_eos = None;
+
+        for (sentence, (sentence_start, sentence_end)) in
+            sentence_ranges(text).into_iter().enumerate()
+        {
+            let sentence_text = &text[sentence_start..sentence_end];
+            let lattice =
+                Lattice::build(sentence_text, dictionary, user_dictionary, &segmenter.mode);
+            let offset = nodes.len();
+
+            // Chain the sentences so that the whole text reads as a single graph
+            if let Some(eos) = previous_eos {
+                connections.push((eos, offset + BOS_NODE));
+            }
+            previous_eos = Some(offset + EOS_NODE);
+
+            for (node_id, node) in lattice.nodes.iter().enumerate() {
+                let word_id = node.word_entry.word_id;
+                let details = match node.kind {
+                    NodeKind::Bos | NodeKind::Eos => Vec::new(),
+                    _ if word_id.is_unknown() => vec!["UNK".to_string()],
+                    NodeKind::User => user_dictionary
+                        .map(|d| d.word_details(word_id.id as usize))
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|d| d.to_string())
+                        .collect(),
+                    _ => dictionary
+                        .word_details(word_id.id as usize)
+                        .iter()
+                        .map(|d| d.to_string())
+                        .collect(),
+                };
+
+                nodes.push(PyLatticeNode {
+                    id: offset + node_id,
+                    kind: match node.kind {
+                        NodeKind::Bos => "BOS",
+                        NodeKind::Eos => "EOS",
+                        NodeKind::Known => "KNOWN",
+                        NodeKind::Unknown => "UNKNOWN",
+                        NodeKind::User => "USER",
+                    }
+                    .to_string(),
+                    surface: sentence_text[node.byte_start..node.byte_end].to_string(),
+                    byte_start: sentence_start + node.byte_start,
+                    byte_end: sentence_start + node.byte_end,
+                    sentence,
+                    word_id: (!word_id.is_unknown()).then_some(word_id.id),
+                    word_cost: node.word_entry.word_cost,
+                    left_context_id: node.word_entry.left_id,
+                    right_context_id: node.word_entry.right_id,
+                    cost: (node.path_cost != i32::MAX).then_some(node.path_cost),
+                    left_node: node.left_node.map(|left| offset + left),
+                    details,
+                });
+
+                for &left in lattice.left_nodes(node_id) {
+                    if lattice.node(left).path_cost != i32::MAX {
+                        connections.push((offset + left, offset + node_id));
+                    }
+                }
+            }
+
+            best_path.push(offset + BOS_NODE);
+            best_path.extend(lattice.best_path().into_iter().map(|id| offset + id));
+            best_path.push(offset + EOS_NODE);
+        }
+
+        PyLattice {
+            text: text.to_string(),
+            nodes,
+            best_path,
+            connections,
+        }
+    }
+
+    fn best_path_edge_set(&self) -> Vec<(usize, usize)> {
+        self.best_path
+            .windows(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect()
+    }
+}
+
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_ranges() {
+        let text = "東京、大阪。\n京都";
+        let ranges = sentence_ranges(text);
+        assert_eq!(ranges, vec![(0, 9), (9, 18), (18, 19), (19, 25)]);
+        assert_eq!(&text[ranges[1].0..ranges[1].1], "大阪。");
+    }
+
+    #[test]
+    fn test_escape_dot() {
+        assert_eq!(escape_dot(r#"a"b\c"#), r#"a\"b\\c"#);
+    }
+}
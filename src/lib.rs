@@ -1,23 +1,38 @@
+pub mod columnar;
+pub mod config;
+pub mod corpus;
 pub mod dictionary;
+pub mod diff;
 pub mod error;
+pub mod evaluate;
+pub mod filter;
+pub mod format;
+pub mod lattice;
 pub mod metadata;
 pub mod mode;
+pub mod registry;
 pub mod schema;
 pub mod segmenter;
+pub mod sentence;
 pub mod token;
 pub mod tokenizer;
+pub mod upos;
 pub mod util;
 
 use pyo3::prelude::*;
 
 use crate::dictionary::{PyDictionary, PyUserDictionary};
+use crate::diff::{PyDiffSpan, PyTokenizationDiff};
 use crate::error::PyLinderaError;
+use crate::evaluate::{PyEvaluation, PySentenceMismatch};
+use crate::lattice::{PyLattice, PyLatticeNode};
 use crate::metadata::{PyCompressionAlgorithm, PyMetadata};
 use crate::mode::{PyMode, PyPenalty};
 use crate::schema::{PyFieldDefinition, PyFieldType, PySchema};
 use crate::segmenter::PySegmenter;
+use crate::sentence::PySentence;
 use crate::token::PyToken;
-use crate::tokenizer::{PyTokenizer, PyTokenizerBuilder};
+use crate::tokenizer::{PyTokenIterator, PyTokenizer, PyTokenizerBuilder};
 
 #[pyfunction]
 pub fn version() -> String {
@@ -31,7 +46,15 @@ fn lindera(module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_class::<PyUserDictionary>()?;
     module.add_class::<PyTokenizerBuilder>()?;
     module.add_class::<PyTokenizer>()?;
+    module.add_class::<PyTokenIterator>()?;
     module.add_class::<PySegmenter>()?;
+    module.add_class::<PySentence>()?;
+    module.add_class::<PyEvaluation>()?;
+    module.add_class::<PySentenceMismatch>()?;
+    module.add_class::<PyTokenizationDiff>()?;
+    module.add_class::<PyDiffSpan>()?;
+    module.add_class::<PyLattice>()?;
+    module.add_class::<PyLatticeNode>()?;
     module.add_class::<PyLinderaError>()?;
     module.add_class::<PyMode>()?;
     module.add_class::<PyPenalty>()?;
@@ -59,6 +82,28 @@ fn lindera(module: &Bound<'_, PyModule>) -> PyResult<()> {
         module
     )?)?;
 
+    // Filter functions
+    module.add_function(wrap_pyfunction!(
+        crate::registry::list_character_filters,
+        module
+    )?)?;
+    module.add_function(wrap_pyfunction!(
+        crate::registry::list_token_filters,
+        module
+    )?)?;
+
+    // Part-of-speech functions
+    module.add_function(wrap_pyfunction!(crate::upos::to_upos, module)?)?;
+
+    // Corpus functions
+    module.add_function(wrap_pyfunction!(crate::corpus::parse_mecab, module)?)?;
+    module.add_function(wrap_pyfunction!(crate::corpus::parse_conllu, module)?)?;
+    module.add_function(wrap_pyfunction!(crate::corpus::parse_wakati, module)?)?;
+
+    // Evaluation functions
+    module.add_function(wrap_pyfunction!(crate::evaluate::evaluate, module)?)?;
+    module.add_function(wrap_pyfunction!(crate::diff::diff_tokenizations, module)?)?;
+
     module.add_function(wrap_pyfunction!(version, module)?)?;
     Ok(())
 }
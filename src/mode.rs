@@ -1,9 +1,12 @@
+use std::str::FromStr;
+
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 
 use lindera::mode::{Mode as LinderaMode, Penalty as LinderaPenalty};
 
-#[pyclass(name = "Mode")]
-#[derive(Debug, Clone, Copy)]
+#[pyclass(name = "Mode", eq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PyMode {
     Normal,
     Decompose,
@@ -66,6 +69,34 @@ impl From<LinderaMode> for PyMode {
     }
 }
 
+/// Mode argument accepted by the Python API, either a `Mode` or its name
+#[derive(FromPyObject)]
+pub enum ModeArg {
+    Mode(PyMode),
+    Name(String),
+}
+
+impl ModeArg {
+    // Resolve the mode, applying the penalty to the decompose mode
+    pub fn into_mode(self, penalty: Option<PyPenalty>) -> PyResult<LinderaMode> {
+        let mode = match self {
+            ModeArg::Mode(mode) => LinderaMode::from(mode),
+            ModeArg::Name(name) => LinderaMode::from_str(&name)
+                .map_err(|err| PyValueError::new_err(format!("Failed to create mode: {err}")))?,
+        };
+
+        match (mode, penalty) {
+            (mode, None) => Ok(mode),
+            (LinderaMode::Decompose(_), Some(penalty)) => {
+                Ok(LinderaMode::Decompose(penalty.into()))
+            }
+            (LinderaMode::Normal, Some(_)) => Err(PyValueError::new_err(
+                "Failed to create mode: penalty is only applicable to decompose mode",
+            )),
+        }
+    }
+}
+
 #[pyclass(name = "Penalty")]
 #[derive(Debug, Clone, Copy)]
 pub struct PyPenalty {
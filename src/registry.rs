@@ -0,0 +1,469 @@
+use pyo3::prelude::*;
+use serde_json::{Value, json};
+
+use crate::util::value_to_pydict;
+
+const JAPANESE_DICTIONARIES: &[&str] = &["ipadic", "ipadic-neologd", "unidic"];
+const KOREAN_DICTIONARIES: &[&str] = &["ko-dic"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgType {
+    Bool,
+    Int,
+    PositiveInt,
+    Str,
+    StrList,
+    StrMap,
+}
+
+impl ArgType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ArgType::Bool => "bool",
+            ArgType::Int => "int",
+            ArgType::PositiveInt => "int",
+            ArgType::Str => "str",
+            ArgType::StrList => "list[str]",
+            ArgType::StrMap => "dict[str, str]",
+        }
+    }
+
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            ArgType::Bool => value.is_boolean(),
+            ArgType::Int => value.is_u64(),
+            ArgType::PositiveInt => value.as_u64().is_some_and(|v| v > 0),
+            ArgType::Str => value.is_string(),
+            ArgType::StrList => value
+                .as_array()
+                .is_some_and(|values| values.iter().all(Value::is_string)),
+            ArgType::StrMap => value
+                .as_object()
+                .is_some_and(|values| values.values().all(Value::is_string)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultValue {
+    None,
+    Bool(bool),
+}
+
+/// Argument accepted by a built-in filter.
+#[derive(Debug)]
+pub struct ArgDef {
+    pub name: &'static str,
+    pub arg_type: ArgType,
+    pub required: bool,
+    pub default: DefaultValue,
+    // Allowed values of a string argument, empty when any value is allowed
+    pub choices: &'static [&'static str],
+}
+
+impl ArgDef {
+    const fn required(name: &'static str, arg_type: ArgType) -> Self {
+        Self {
+            name,
+            arg_type,
+            required: true,
+            default: DefaultValue::None,
+            choices: &[],
+        }
+    }
+
+    const fn optional(name: &'static str, arg_type: ArgType, default: DefaultValue) -> Self {
+        Self {
+            name,
+            arg_type,
+            required: false,
+            default,
+            choices: &[],
+        }
+    }
+
+    const fn choice(name: &'static str, choices: &'static [&'static str]) -> Self {
+        Self {
+            name,
+            arg_type: ArgType::Str,
+            required: true,
+            default: DefaultValue::None,
+            choices,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        let default = match self.default {
+            DefaultValue::None => Value::Null,
+            DefaultValue::Bool(value) => json!(value),
+        };
+        let mut value = json!({
+            "name": self.name,
+            "type": self.arg_type.name(),
+            "required": self.required,
+            "default": default,
+        });
+        if !self.choices.is_empty() {
+            value["choices"] = json!(self.choices);
+        }
+
+        value
+    }
+
+    fn validate(&self, value: &Value) -> Result<(), String> {
+        if !self.arg_type.accepts(value) {
+            let expected = match self.arg_type {
+                ArgType::PositiveInt => "a positive int",
+                arg_type => arg_type.name(),
+            };
+            return Err(format!("'{}' must be {expected}, got {value}", self.name));
+        }
+        if !self.choices.is_empty() && !self.choices.iter().any(|choice| value == choice) {
+            return Err(format!(
+                "'{}' must be one of {}, got {value}",
+                self.name,
+                self.choices.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Built-in filter of Lindera.
+#[derive(Debug)]
+pub struct FilterDef {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub args: &'static [ArgDef],
+    // Dictionaries whose tokens the filter understands, `None` when it works with any dictionary
+    pub dictionaries: Option<&'static [&'static str]>,
+}
+
+impl FilterDef {
+    pub fn to_value(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "args": self.args.iter().map(ArgDef::to_value).collect::<Vec<_>>(),
+            "dictionaries": self.dictionaries,
+        })
+    }
+
+    // Check the arguments of the filter before it is loaded,
+    // which only reports a generic error or silently ignores mistyped arguments
+    pub fn validate(&self, args: &Value) -> Result<(), String> {
+        let Some(args) = args.as_object() else {
+            return Err(format!("args must be a dict, got {args}"));
+        };
+
+        for (name, value) in args {
+            let Some(arg) = self.args.iter().find(|arg| arg.name == name) else {
+                let accepted = self.args.iter().map(|arg| arg.name).collect::<Vec<_>>();
+                return Err(if accepted.is_empty() {
+                    format!("unknown argument '{name}', the filter takes no arguments")
+                } else {
+                    format!(
+                        "unknown argument '{name}', accepted arguments are {}",
+                        accepted.join(", ")
+                    )
+                });
+            };
+            arg.validate(value)?;
+        }
+
+        if let Some(arg) = self
+            .args
+            .iter()
+            .find(|arg| arg.required && !args.contains_key(arg.name))
+        {
+            return Err(format!("missing required argument '{}'", arg.name));
+        }
+
+        Ok(())
+    }
+}
+
+/// Table of the built-in filters of one chain.
+pub struct FilterRegistry {
+    // "character filter" or "token filter", used in error messages
+    pub kind: &'static str,
+    pub filters: &'static [FilterDef],
+}
+
+impl FilterRegistry {
+    pub fn get(&self, name: &str) -> Option<&'static FilterDef> {
+        self.filters.iter().find(|filter| filter.name == name)
+    }
+
+    pub fn validate(&self, name: &str, args: &Value) -> Result<(), String> {
+        let filter = self.get(name).ok_or_else(|| {
+            let names = self
+                .filters
+                .iter()
+                .map(|filter| filter.name)
+                .collect::<Vec<_>>();
+            format!(
+                "unknown {} '{name}', available filters are {}",
+                self.kind,
+                names.join(", ")
+            )
+        })?;
+
+        filter
+            .validate(args)
+            .map_err(|err| format!("invalid args for {} '{name}': {err}", self.kind))
+    }
+}
+
+pub const CHARACTER_FILTERS: FilterRegistry = FilterRegistry {
+    kind: "character filter",
+    filters: &[
+        FilterDef {
+            name: "japanese_iteration_mark",
+            description: "Replaces Japanese iteration marks with the characters they repeat.",
+            args: &[
+                ArgDef::optional("normalize_kanji", ArgType::Bool, DefaultValue::Bool(false)),
+                ArgDef::optional("normalize_kana", ArgType::Bool, DefaultValue::Bool(false)),
+            ],
+            dictionaries: None,
+        },
+        FilterDef {
+            name: "mapping",
+            description: "Replaces strings according to a mapping table.",
+            args: &[ArgDef::required("mapping", ArgType::StrMap)],
+            dictionaries: None,
+        },
+        FilterDef {
+            name: "regex",
+            description: "Replaces matches of a regular expression.",
+            args: &[
+                ArgDef::required("pattern", ArgType::Str),
+                ArgDef::required("replacement", ArgType::Str),
+            ],
+            dictionaries: None,
+        },
+        FilterDef {
+            name: "unicode_normalize",
+            description: "Applies Unicode normalization.",
+            args: &[ArgDef::choice("kind", &["nfc", "nfd", "nfkc", "nfkd"])],
+            dictionaries: None,
+        },
+    ],
+};
+
+pub const TOKEN_FILTERS: FilterRegistry = FilterRegistry {
+    kind: "token filter",
+    filters: &[
+        FilterDef {
+            name: "japanese_base_form",
+            description: "Replaces the text of tokens with their base form.",
+            args: &[],
+            dictionaries: Some(JAPANESE_DICTIONARIES),
+        },
+        FilterDef {
+            name: "japanese_compound_word",
+            description: "Joins consecutive tokens with the given part-of-speech tags into one token.",
+            args: &[
+                ArgDef::required("tags", ArgType::StrList),
+                ArgDef::optional("new_tag", ArgType::Str, DefaultValue::None),
+            ],
+            dictionaries: Some(JAPANESE_DICTIONARIES),
+        },
+        FilterDef {
+            name: "japanese_kana",
+            description: "Converts the text of tokens to hiragana or katakana.",
+            args: &[ArgDef::choice("kind", &["hiragana", "katakana"])],
+            dictionaries: Some(JAPANESE_DICTIONARIES),
+        },
+        FilterDef {
+            name: "japanese_katakana_stem",
+            description: "Removes the trailing prolonged sound mark from katakana tokens of at least `min` characters.",
+            args: &[ArgDef::required("min", ArgType::PositiveInt)],
+            dictionaries: Some(JAPANESE_DICTIONARIES),
+        },
+        FilterDef {
+            name: "japanese_keep_tags",
+            description: "Keeps only tokens with the given part-of-speech tags.",
+            args: &[ArgDef::required("tags", ArgType::StrList)],
+            dictionaries: Some(JAPANESE_DICTIONARIES),
+        },
+        FilterDef {
+            name: "japanese_number",
+            description: "Converts Japanese numerals to Arabic numerals.",
+            args: &[ArgDef::optional(
+                "tags",
+                ArgType::StrList,
+                DefaultValue::None,
+            )],
+            dictionaries: Some(JAPANESE_DICTIONARIES),
+        },
+        FilterDef {
+            name: "japanese_reading_form",
+            description: "Replaces the text of tokens with their reading.",
+            args: &[],
+            dictionaries: Some(JAPANESE_DICTIONARIES),
+        },
+        FilterDef {
+            name: "japanese_stop_tags",
+            description: "Removes tokens with the given part-of-speech tags.",
+            args: &[ArgDef::required("tags", ArgType::StrList)],
+            dictionaries: Some(JAPANESE_DICTIONARIES),
+        },
+        FilterDef {
+            name: "keep_words",
+            description: "Keeps only the given words.",
+            args: &[ArgDef::required("words", ArgType::StrList)],
+            dictionaries: None,
+        },
+        FilterDef {
+            name: "korean_keep_tags",
+            description: "Keeps only tokens with the given part-of-speech tags.",
+            args: &[ArgDef::required("tags", ArgType::StrList)],
+            dictionaries: Some(KOREAN_DICTIONARIES),
+        },
+        FilterDef {
+            name: "korean_reading_form",
+            description: "Replaces the text of tokens with their reading.",
+            args: &[],
+            dictionaries: Some(KOREAN_DICTIONARIES),
+        },
+        FilterDef {
+            name: "korean_stop_tags",
+            description: "Removes tokens with the given part-of-speech tags.",
+            args: &[ArgDef::required("tags", ArgType::StrList)],
+            dictionaries: Some(KOREAN_DICTIONARIES),
+        },
+        FilterDef {
+            name: "length",
+            description: "Keeps only tokens whose length in characters is within `min` and `max`.",
+            args: &[
+                ArgDef::optional("min", ArgType::Int, DefaultValue::None),
+                ArgDef::optional("max", ArgType::Int, DefaultValue::None),
+            ],
+            dictionaries: None,
+        },
+        FilterDef {
+            name: "lowercase",
+            description: "Converts the text of tokens to lowercase.",
+            args: &[],
+            dictionaries: None,
+        },
+        FilterDef {
+            name: "mapping",
+            description: "Replaces strings in the text of tokens according to a mapping table.",
+            args: &[ArgDef::required("mapping", ArgType::StrMap)],
+            dictionaries: None,
+        },
+        FilterDef {
+            name: "remove_diacritical_mark",
+            description: "Removes diacritical marks from the text of tokens.",
+            args: &[ArgDef::optional(
+                "japanese",
+                ArgType::Bool,
+                DefaultValue::Bool(false),
+            )],
+            dictionaries: None,
+        },
+        FilterDef {
+            name: "stop_words",
+            description: "Removes the given words.",
+            args: &[ArgDef::required("words", ArgType::StrList)],
+            dictionaries: None,
+        },
+        FilterDef {
+            name: "uppercase",
+            description: "Converts the text of tokens to uppercase.",
+            args: &[],
+            dictionaries: None,
+        },
+    ],
+};
+
+#[pyfunction]
+pub fn list_character_filters(py: Python<'_>) -> PyResult<Vec<Py<PyAny>>> {
+    list_filters(py, &CHARACTER_FILTERS)
+}
+
+#[pyfunction]
+pub fn list_token_filters(py: Python<'_>) -> PyResult<Vec<Py<PyAny>>> {
+    list_filters(py, &TOKEN_FILTERS)
+}
+
+fn list_filters(py: Python<'_>, registry: &FilterRegistry) -> PyResult<Vec<Py<PyAny>>> {
+    registry
+        .filters
+        .iter()
+        .map(|filter| value_to_pydict(py, &filter.to_value()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use lindera::character_filter::CharacterFilterLoader;
+    use lindera::token_filter::TokenFilterLoader;
+
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let args = json!({ "normalize_kanji": true, "normalize_kana": false });
+        assert!(
+            CHARACTER_FILTERS
+                .validate("japanese_iteration_mark", &args)
+                .is_ok()
+        );
+
+        let args = json!({ "normalize_kanji": "true" });
+        assert!(
+            CHARACTER_FILTERS
+                .validate("japanese_iteration_mark", &args)
+                .unwrap_err()
+                .contains("'normalize_kanji' must be bool")
+        );
+
+        assert!(
+            CHARACTER_FILTERS
+                .validate("unicode_normalize", &json!({ "kind": "nfkx" }))
+                .unwrap_err()
+                .contains("must be one of nfc, nfd, nfkc, nfkd")
+        );
+        assert!(
+            TOKEN_FILTERS
+                .validate("japanese_stop_tags", &json!({}))
+                .unwrap_err()
+                .contains("missing required argument 'tags'")
+        );
+        assert!(
+            TOKEN_FILTERS
+                .validate("lowercase", &json!({ "min": 1 }))
+                .unwrap_err()
+                .contains("unknown argument 'min'")
+        );
+        assert!(
+            TOKEN_FILTERS
+                .validate("lowercasee", &json!({}))
+                .unwrap_err()
+                .contains("unknown token filter 'lowercasee'")
+        );
+    }
+
+    #[test]
+    fn test_registry_matches_lindera() {
+        // Every registered filter is known to Lindera
+        for filter in CHARACTER_FILTERS.filters {
+            let error = CharacterFilterLoader::load_from_value(filter.name, &json!({}))
+                .err()
+                .map(|err| err.to_string())
+                .unwrap_or_default();
+            assert!(!error.contains("unsupported"), "{}", filter.name);
+        }
+        for filter in TOKEN_FILTERS.filters {
+            let error = TokenFilterLoader::load_from_value(filter.name, &json!({}))
+                .err()
+                .map(|err| err.to_string())
+                .unwrap_or_default();
+            assert!(!error.contains("unsupported"), "{}", filter.name);
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use pyo3::prelude::*;
+use pyo3::types::PyType;
 
 use lindera::dictionary::{FieldDefinition, FieldType, Schema};
 
@@ -119,7 +120,7 @@ impl From<PyFieldDefinition> for FieldDefinition {
     }
 }
 
-#[pyclass(name = "Schema")]
+#[pyclass(name = "Schema", module = "lindera")]
 #[derive(Debug, Clone)]
 pub struct PySchema {
     #[pyo3(get)]
@@ -240,6 +241,10 @@ impl PySchema {
     fn __len__(&self) -> usize {
         self.fields.len()
     }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> (Bound<'py, PyType>, (Vec<String>,)) {
+        (slf.get_type(), (slf.borrow().fields.clone(),))
+    }
 }
 
 impl PySchema {
@@ -252,6 +257,26 @@ impl PySchema {
     }
 }
 
+// Schema fields before the token details: surface, left_context_id, right_context_id and cost
+pub const COMMON_FIELD_COUNT: usize = 4;
+
+// Resolve a field of the token details, given either as an index into the details
+// or as a field name of the dictionary schema
+pub fn detail_index(schema: &Schema, field: &str) -> Result<usize, String> {
+    if let Ok(index) = field.parse::<usize>() {
+        return Ok(index);
+    }
+
+    match schema.get_field_index(field) {
+        Some(index) if index >= COMMON_FIELD_COUNT => Ok(index - COMMON_FIELD_COUNT),
+        Some(_) => Err(format!("field '{field}' is not a feature of the token")),
+        None => Err(format!(
+            "unknown field '{field}', available fields are {}",
+            schema.get_custom_fields().join(", ")
+        )),
+    }
+}
+
 impl From<PySchema> for Schema {
     fn from(schema: PySchema) -> Self {
         Schema::new(schema.fields)
@@ -1,69 +1,204 @@
-use std::borrow::Cow;
-use std::str::FromStr;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyDict, PyType};
+use serde_json::{Value, json};
 
+use lindera::dictionary::Schema;
 use lindera::mode::Mode;
 use lindera::segmenter::Segmenter;
 
+use crate::config::{
+    mode_to_value, parse_json_config, parse_yaml_config, read_config_file, segmenter_section,
+};
 use crate::dictionary::{PyDictionary, PyUserDictionary};
-use crate::token::PyToken;
-use crate::util::pydict_to_value;
+use crate::lattice::{PyLattice, segment_nbest, segment_with_entries};
+use crate::mode::{ModeArg, PyMode, PyPenalty};
+use crate::token::{PyToken, TextIndex, dictionary_schema};
+use crate::util::{parallel_map, pydict_to_value, value_to_pydict};
 
-#[pyclass(name = "Segmenter")]
+/// Segmenter that splits text into tokens using a dictionary.
+///
+/// The GIL is released while text is being segmented, and a single `Segmenter`
+/// can safely be shared and used concurrently by multiple Python threads.
+#[pyclass(name = "Segmenter", module = "lindera")]
 #[derive(Clone)]
 pub struct PySegmenter {
     pub inner: Segmenter,
+    // Dictionary schema shared by the tokens
+    pub schema: Arc<Schema>,
+    // Segmenter section of the configuration this segmenter was created from
+    pub config: Value,
 }
 
 #[pymethods]
 impl PySegmenter {
     #[new]
-    #[pyo3(signature = (mode, dictionary, user_dictionary=None))]
+    #[pyo3(signature = (mode, dictionary, user_dictionary=None, penalty=None))]
     fn new(
-        mode: &str,
+        mode: ModeArg,
         dictionary: PyDictionary,
         user_dictionary: Option<PyUserDictionary>,
+        penalty: Option<PyPenalty>,
     ) -> PyResult<Self> {
-        let m = Mode::from_str(mode)
-            .map_err(|err| PyValueError::new_err(format!("Failed to create mode: {err}")))?;
+        let m = mode.into_mode(penalty)?;
+        let mut config = json!({
+            "mode": mode_to_value(&m),
+            "dictionary": dictionary.uri,
+        });
+        if let Some(user_dictionary) = &user_dictionary {
+            config["user_dictionary"] = json!(user_dictionary.uri);
+        }
+
         let d = dictionary.inner;
         let u = user_dictionary.map(|d| d.inner);
 
         let segmenter = Segmenter::new(m, d, u);
 
-        Ok(Self { inner: segmenter })
+        Ok(Self {
+            schema: dictionary_schema(&segmenter),
+            inner: segmenter,
+            config,
+        })
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (config))]
+    fn from_config(config: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_value(pydict_to_value(config)?)
     }
 
+    /// Creates a segmenter from a dict holding either the segmenter configuration
+    /// or a whole tokenizer configuration with a `segmenter` section, the same as `from_config`.
+    #[staticmethod]
     #[pyo3(signature = (config))]
-    #[allow(clippy::wrong_self_convention)]
-    fn from_config(&self, config: &Bound<'_, PyDict>) -> PyResult<Self> {
-        let config_value = pydict_to_value(config)?;
-        let segmenter = Segmenter::from_config(&config_value)
-            .map_err(|err| PyValueError::new_err(format!("Failed to create tokenizer: {err}")))?;
+    fn from_dict(config: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_config(config)
+    }
+
+    /// Creates a segmenter from a YAML string.
+    #[staticmethod]
+    #[pyo3(signature = (yaml))]
+    fn from_yaml(yaml: &str) -> PyResult<Self> {
+        Self::from_value(parse_yaml_config(yaml)?)
+    }
+
+    /// Creates a segmenter from a JSON string.
+    #[staticmethod]
+    #[pyo3(signature = (json))]
+    fn from_json(json: &str) -> PyResult<Self> {
+        Self::from_value(parse_json_config(json)?)
+    }
+
+    /// Creates a segmenter from a YAML or JSON file.
+    #[staticmethod]
+    #[pyo3(signature = (path))]
+    fn from_path(path: PathBuf) -> PyResult<Self> {
+        Self::from_value(read_config_file(&path)?)
+    }
+
+    // The segmenter is rebuilt from its configuration when unpickled
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (Py<PyAny>,))> {
+        let config = value_to_pydict(slf.py(), &slf.borrow().config)?;
+        Ok((slf.get_type().getattr("_from_state")?, (config,)))
+    }
+
+    #[classmethod]
+    fn _from_state(_cls: &Bound<'_, PyType>, config: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_value(pydict_to_value(config)?)
+    }
+
+    #[getter]
+    fn mode(&self) -> PyMode {
+        self.inner.mode.clone().into()
+    }
+
+    /// Penalty of the decompose mode, or `None` in normal mode.
+    #[getter]
+    fn penalty(&self) -> Option<PyPenalty> {
+        match &self.inner.mode {
+            Mode::Normal => None,
+            Mode::Decompose(penalty) => Some(penalty.clone().into()),
+        }
+    }
+
+    #[pyo3(signature = (text))]
+    fn segment(&self, py: Python<'_>, text: String) -> PyResult<Vec<PyToken>> {
+        py.detach(|| self.segment_text(&text))
+    }
 
-        Ok(Self { inner: segmenter })
+    #[pyo3(signature = (texts))]
+    fn segment_batch(&self, py: Python<'_>, texts: Vec<String>) -> PyResult<Vec<Vec<PyToken>>> {
+        py.detach(|| {
+            parallel_map(&texts, |text| self.segment_text(text))
+                .into_iter()
+                .collect()
+        })
     }
 
     #[pyo3(signature = (text))]
-    fn segment(&self, text: &str) -> PyResult<Vec<PyToken>> {
-        let mut tokens = self
-            .inner
-            .segment(Cow::Borrowed(text))
-            .map_err(|err| PyValueError::new_err(format!("Failed to tokenize text: {err}")))?;
+    fn lattice(&self, py: Python<'_>, text: String) -> PyLattice {
+        py.detach(|| PyLattice::build(&self.inner, &text))
+    }
+
+    #[pyo3(signature = (text, n))]
+    fn segment_nbest(
+        &self,
+        py: Python<'_>,
+        text: String,
+        n: usize,
+    ) -> PyResult<Vec<(Vec<PyToken>, i32)>> {
+        if n == 0 {
+            return Err(PyValueError::new_err("n must be greater than 0"));
+        }
+
+        py.detach(|| {
+            let index = TextIndex::new(&text, false);
+            Ok(segment_nbest(&self.inner, &text, n)
+                .into_iter()
+                .map(|(mut tokens, entries, cost)| {
+                    (
+                        tokens
+                            .iter_mut()
+                            .map(|token| {
+                                let entry = entries.get(token);
+                                PyToken::from_token(token, &index, &self.schema, entry)
+                            })
+                            .collect(),
+                        cost,
+                    )
+                })
+                .collect())
+        })
+    }
+}
+
+impl PySegmenter {
+    // Internal helper function to create PySegmenter from a configuration value
+    fn from_value(config: Value) -> PyResult<Self> {
+        let config = segmenter_section(config);
+        let segmenter = Segmenter::from_config(&config)
+            .map_err(|err| PyValueError::new_err(format!("Failed to create segmenter: {err}")))?;
+
+        Ok(Self {
+            schema: dictionary_schema(&segmenter),
+            inner: segmenter,
+            config,
+        })
+    }
+
+    fn segment_text(&self, text: &str) -> PyResult<Vec<PyToken>> {
+        let (mut tokens, entries) = segment_with_entries(&self.inner, text);
 
+        let index = TextIndex::new(text, false);
         Ok(tokens
             .iter_mut()
-            .map(|t| PyToken {
-                #[allow(clippy::suspicious_to_owned)]
-                text: t.text.to_owned().to_string(),
-                byte_start: t.byte_start,
-                byte_end: t.byte_end,
-                position: t.position,
-                position_length: t.position_length,
-                details: t.details().iter().map(|d| d.to_string()).collect(),
+            .map(|token| {
+                let entry = entries.get(token);
+                PyToken::from_token(token, &index, &self.schema, entry)
             })
             .collect())
     }
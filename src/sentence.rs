@@ -0,0 +1,285 @@
+use std::ops::Range;
+
+use pyo3::prelude::*;
+use pyo3::types::PyType;
+
+use crate::token::TextIndex;
+
+/// Characters that end a sentence unless other terminators are given.
+pub const DEFAULT_TERMINATORS: &[&str] = &["。", "．", "！", "？", "!", "?"];
+
+// Characters that stay with the sentence when they follow a terminator, as in 本当！？… or 「はい。」
+const TRAILING_CHARS: &[char] = &[
+    '」', '』', '）', ')', '】', '〕', '〉', '》', '］', ']', '”', '’', '"', '\'', '…', '‥',
+];
+
+// Opening and closing brackets, within which terminators do not end a sentence
+const BRACKETS: &[(char, char)] = &[
+    ('「', '」'),
+    ('『', '』'),
+    ('（', '）'),
+    ('(', ')'),
+    ('【', '】'),
+    ('〔', '〕'),
+    ('〈', '〉'),
+    ('《', '》'),
+    ('［', '］'),
+    ('[', ']'),
+    ('“', '”'),
+];
+
+#[pyclass(name = "Sentence", module = "lindera")]
+#[derive(Clone)]
+pub struct PySentence {
+    #[pyo3(get)]
+    pub text: String,
+    #[pyo3(get)]
+    pub byte_start: usize,
+    #[pyo3(get)]
+    pub byte_end: usize,
+    #[pyo3(get)]
+    pub char_start: usize,
+    #[pyo3(get)]
+    pub char_end: usize,
+    #[pyo3(get)]
+    pub utf16_start: Option<usize>,
+    #[pyo3(get)]
+    pub utf16_end: Option<usize>,
+}
+
+#[pymethods]
+impl PySentence {
+    #[new]
+    #[pyo3(signature = (text, byte_start, byte_end, char_start, char_end, utf16_start=None, utf16_end=None))]
+    pub fn new(
+        text: String,
+        byte_start: usize,
+        byte_end: usize,
+        char_start: usize,
+        char_end: usize,
+        utf16_start: Option<usize>,
+        utf16_end: Option<usize>,
+    ) -> Self {
+        PySentence {
+            text,
+            byte_start,
+            byte_end,
+            char_start,
+            char_end,
+            utf16_start,
+            utf16_end,
+        }
+    }
+
+    fn __str__(&self) -> String {
+        self.text.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Sentence(text='{}', byte_start={}, byte_end={}, char_start={}, char_end={})",
+            self.text, self.byte_start, self.byte_end, self.char_start, self.char_end
+        )
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> (
+        Bound<'py, PyType>,
+        (
+            String,
+            usize,
+            usize,
+            usize,
+            usize,
+            Option<usize>,
+            Option<usize>,
+        ),
+    ) {
+        let sentence = slf.borrow();
+        (
+            slf.get_type(),
+            (
+                sentence.text.clone(),
+                sentence.byte_start,
+                sentence.byte_end,
+                sentence.char_start,
+                sentence.char_end,
+                sentence.utf16_start,
+                sentence.utf16_end,
+            ),
+        )
+    }
+}
+
+impl PySentence {
+    // Internal helper function to create PySentence from a byte range of the text
+    pub fn from_range(text: &str, range: Range<usize>, index: &TextIndex) -> Self {
+        PySentence {
+            text: text[range.clone()].to_string(),
+            byte_start: range.start,
+            byte_end: range.end,
+            char_start: index.char_offset(range.start),
+            char_end: index.char_offset(range.end),
+            utf16_start: index.utf16_offset(range.start),
+            utf16_end: index.utf16_offset(range.end),
+        }
+    }
+}
+
+/// Splits a text into sentences and returns their byte ranges.
+///
+/// A sentence ends after a terminator, together with the terminators, closing brackets and ellipses
+/// that directly follow it, or at a line break when `split_on_newlines` is set.
+/// Terminators inside brackets such as 「」, 『』 and （） do not end a sentence, nor does a closing bracket
+/// followed by more text on the same line, so that 「はい。」と答えた！ is a single sentence.
+/// Brackets left open at the end of a line are closed there.
+/// Surrounding whitespace is not part of a sentence, and blank sentences are skipped.
+pub fn split_sentences<T: AsRef<str>>(
+    text: &str,
+    terminators: &[T],
+    split_on_newlines: bool,
+) -> Vec<Range<usize>> {
+    let terminator_at = |offset: usize| {
+        terminators
+            .iter()
+            .map(AsRef::as_ref)
+            .find(|terminator| !terminator.is_empty() && text[offset..].starts_with(terminator))
+            .map(str::len)
+    };
+
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    // Number of brackets open at the current offset
+    let mut depth: usize = 0;
+    while let Some(c) = text[offset..].chars().next() {
+        if c == '\n' || c == '\r' {
+            depth = 0;
+            if split_on_newlines {
+                push_sentence(&mut sentences, text, start..offset);
+                offset += c.len_utf8();
+                start = offset;
+                continue;
+            }
+        } else if let Some(len) = terminator_at(offset).filter(|_| depth == 0) {
+            offset += len;
+            let mut after_bracket = false;
+            while let Some(c) = text[offset..].chars().next() {
+                if let Some(len) = terminator_at(offset) {
+                    offset += len;
+                    after_bracket = false;
+                } else if TRAILING_CHARS.contains(&c) {
+                    offset += c.len_utf8();
+                    after_bracket = BRACKETS.iter().any(|&(_, close)| close == c);
+                } else {
+                    break;
+                }
+            }
+            if !(after_bracket && continues_line(&text[offset..])) {
+                push_sentence(&mut sentences, text, start..offset);
+                start = offset;
+            }
+            continue;
+        } else if BRACKETS.iter().any(|&(open, _)| open == c) {
+            depth += 1;
+        } else if BRACKETS.iter().any(|&(_, close)| close == c) {
+            depth = depth.saturating_sub(1);
+        }
+        offset += c.len_utf8();
+    }
+    push_sentence(&mut sentences, text, start..text.len());
+
+    sentences
+}
+
+// Whether text other than whitespace follows before the end of the line
+fn continues_line(rest: &str) -> bool {
+    rest.chars()
+        .take_while(|&c| c != '\n' && c != '\r')
+        .any(|c| !c.is_whitespace())
+}
+
+fn push_sentence(sentences: &mut Vec<Range<usize>>, text: &str, range: Range<usize>) {
+    let sentence = &text[range.clone()];
+    let trimmed = sentence.trim_start();
+    let start = range.start + (sentence.len() - trimmed.len());
+    let end = start + trimmed.trim_end().len();
+    if start < end {
+        sentences.push(start..end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentences<'a>(text: &'a str, terminators: &[&str], split_on_newlines: bool) -> Vec<&'a str> {
+        split_sentences(text, terminators, split_on_newlines)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        assert_eq!(
+            sentences(
+                "今日は晴れです。明日は？　「はい。」と答えた！",
+                DEFAULT_TERMINATORS,
+                true
+            ),
+            vec!["今日は晴れです。", "明日は？", "「はい。」と答えた！"]
+        );
+
+        // Terminators inside brackets do not end a sentence, and a quote ending the line does
+        assert_eq!(
+            sentences(
+                "（注：省略。）以上です。「本当？」\n次へ。",
+                DEFAULT_TERMINATORS,
+                true
+            ),
+            vec!["（注：省略。）以上です。", "「本当？」", "次へ。"]
+        );
+
+        // A closing bracket without its opening one stays with the sentence it continues
+        assert_eq!(
+            sentences("本当！」と彼は言った。次。", DEFAULT_TERMINATORS, true),
+            vec!["本当！」と彼は言った。", "次。"]
+        );
+
+        // A bracket left open does not carry over to the next line
+        assert_eq!(
+            sentences("「開いたまま。\n次。終わり。", DEFAULT_TERMINATORS, false),
+            vec!["「開いたまま。\n次。", "終わり。"]
+        );
+
+        // Consecutive terminators and ellipses stay with the sentence
+        assert_eq!(
+            sentences("本当！？……嘘でしょ。。まあいい", DEFAULT_TERMINATORS, true),
+            vec!["本当！？……", "嘘でしょ。。", "まあいい"]
+        );
+
+        // An ellipsis alone does not end a sentence
+        assert_eq!(
+            sentences("えっと…それで。", DEFAULT_TERMINATORS, true),
+            vec!["えっと…それで。"]
+        );
+
+        assert_eq!(
+            sentences("一行目\r\n\n二行目\n", DEFAULT_TERMINATORS, true),
+            vec!["一行目", "二行目"]
+        );
+        assert_eq!(
+            sentences("一行目\n二行目。", DEFAULT_TERMINATORS, false),
+            vec!["一行目\n二行目。"]
+        );
+
+        assert_eq!(
+            sentences("Hello. How are you? Fine", &[".", "?"], true),
+            vec!["Hello.", "How are you?", "Fine"]
+        );
+        assert!(sentences("  \n ", DEFAULT_TERMINATORS, true).is_empty());
+    }
+}
@@ -1,7 +1,27 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use pyo3::IntoPyObjectExt;
+use pyo3::exceptions::PyKeyError;
 use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple, PyType};
+
+use lindera::dictionary::Schema;
+use lindera::segmenter::Segmenter;
+use lindera::token::Token;
+use lindera_dictionary::viterbi::WordEntry;
+
+use crate::schema::{COMMON_FIELD_COUNT, PySchema, detail_index};
+use crate::upos::{PosTagSet, to_upos_tag};
+
+// Schema fields that the attribute shortcuts of a token look up, in order of preference,
+// so that they work across dictionaries
+const POS_FIELDS: &[&str] = &["part_of_speech", "part_of_speech_tag"];
+const BASE_FORM_FIELDS: &[&str] = &["base_form", "orthographic_base_form"];
+const READING_FIELDS: &[&str] = &["reading", "pinyin"];
 
 // Simplified Token representation for Python
-#[pyclass(name = "Token")]
+#[pyclass(name = "Token", module = "lindera")]
 #[derive(Clone)]
 pub struct PyToken {
     #[pyo3(get)]
@@ -10,17 +30,45 @@ pub struct PyToken {
     pub byte_start: usize,
     #[pyo3(get)]
     pub byte_end: usize,
+    // Character offsets, or None for a token created by hand whose offsets cannot be derived
+    #[pyo3(get)]
+    pub char_start: Option<usize>,
+    #[pyo3(get)]
+    pub char_end: Option<usize>,
+    #[pyo3(get)]
+    pub utf16_start: Option<usize>,
+    #[pyo3(get)]
+    pub utf16_end: Option<usize>,
     #[pyo3(get)]
     pub position: usize,
     #[pyo3(get)]
     pub position_length: usize,
     #[pyo3(get)]
     pub details: Vec<String>,
+    // Word id in the system or user dictionary, or None for an unknown word
+    #[pyo3(get)]
+    pub word_id: Option<u32>,
+    #[pyo3(get)]
+    pub is_unknown: bool,
+    #[pyo3(get)]
+    pub is_user_dictionary: bool,
+    // Cost and context ids of the dictionary entry, from the unknown dictionary for an unknown word,
+    // or None when a token filter created the word
+    #[pyo3(get)]
+    pub word_cost: Option<i16>,
+    #[pyo3(get)]
+    pub left_context_id: Option<u16>,
+    #[pyo3(get)]
+    pub right_context_id: Option<u16>,
+    // Schema of the dictionary the token comes from, naming the details
+    pub schema: Option<Arc<Schema>>,
 }
 
 #[pymethods]
 impl PyToken {
     #[new]
+    #[pyo3(signature = (text, byte_start, byte_end, position, position_length, details, char_start=None, char_end=None, utf16_start=None, utf16_end=None, schema=None, word_id=None, is_unknown=false, is_user_dictionary=false, word_cost=None, left_context_id=None, right_context_id=None))]
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         text: String,
         byte_start: usize,
@@ -28,17 +76,118 @@ impl PyToken {
         position: usize,
         position_length: usize,
         details: Vec<String>,
+        char_start: Option<usize>,
+        char_end: Option<usize>,
+        utf16_start: Option<usize>,
+        utf16_end: Option<usize>,
+        schema: Option<PySchema>,
+        word_id: Option<u32>,
+        is_unknown: bool,
+        is_user_dictionary: bool,
+        word_cost: Option<i16>,
+        left_context_id: Option<u16>,
+        right_context_id: Option<u16>,
     ) -> Self {
+        // A token at the start of the text starts at character 0, and one whose text spans
+        // its bytes ends its character count later
+        let char_start = char_start.or((byte_start == 0).then_some(0));
+        let char_end = char_end.or_else(|| {
+            let spans_bytes = byte_end.checked_sub(byte_start) == Some(text.len());
+            char_start
+                .filter(|_| spans_bytes)
+                .map(|start| start + text.chars().count())
+        });
         PyToken {
             text,
             byte_start,
             byte_end,
+            char_start,
+            char_end,
+            utf16_start,
+            utf16_end,
             position,
             position_length,
             details,
+            word_id,
+            is_unknown,
+            is_user_dictionary,
+            word_cost,
+            left_context_id,
+            right_context_id,
+            schema: schema.map(|schema| Arc::new(schema.into())),
         }
     }
 
+    /// Schema of the dictionary the token comes from, or `None` for a token created by hand.
+    #[getter]
+    fn schema(&self) -> Option<PySchema> {
+        self.schema
+            .as_ref()
+            .map(|schema| PySchema::from(schema.as_ref().clone()))
+    }
+
+    /// Returns the detail named `field_name` in the dictionary schema, such as `"reading"`.
+    ///
+    /// Returns `None` when the token has fewer details than the schema, as unknown words may,
+    /// and raises `KeyError` when the schema has no such field.
+    #[pyo3(signature = (field_name))]
+    fn get(&self, field_name: &str) -> PyResult<Option<String>> {
+        if field_name == "surface" {
+            return Ok(Some(self.text.clone()));
+        }
+        let schema = self.schema.as_ref().ok_or_else(|| {
+            PyKeyError::new_err(format!(
+                "Token has no schema to look up field '{field_name}'"
+            ))
+        })?;
+        let index = detail_index(schema, field_name).map_err(PyKeyError::new_err)?;
+
+        Ok(self.details.get(index).cloned())
+    }
+
+    /// Details of the token as a dict keyed by the field names of the dictionary schema.
+    #[getter]
+    fn features<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let features = PyDict::new(py);
+        if let Some(schema) = &self.schema {
+            for (name, value) in schema.get_custom_fields().iter().zip(&self.details) {
+                features.set_item(name, value)?;
+            }
+        }
+
+        Ok(features)
+    }
+
+    /// Part of speech, from the `part_of_speech` or `part_of_speech_tag` field.
+    #[getter]
+    fn pos(&self) -> Option<String> {
+        self.lookup(POS_FIELDS)
+    }
+
+    /// Base form, from the `base_form` or `orthographic_base_form` field.
+    #[getter]
+    pub fn base_form(&self) -> Option<String> {
+        self.lookup(BASE_FORM_FIELDS)
+    }
+
+    /// Reading, from the `reading` or `pinyin` field.
+    #[getter]
+    fn reading(&self) -> Option<String> {
+        self.lookup(READING_FIELDS)
+    }
+
+    /// Universal Dependencies UPOS tag, from the `upos` field when the schema has one as CoNLL-U does,
+    /// otherwise mapped from the part of speech, or `None` when the dictionary of the token is not known.
+    #[getter]
+    pub fn upos(&self) -> Option<String> {
+        let schema = self.schema.as_ref()?;
+        if let Ok(index) = detail_index(schema, "upos") {
+            return self.details.get(index).cloned();
+        }
+        let tag_set = PosTagSet::from_schema(schema)?;
+        Some(to_upos_tag(tag_set, &self.details).to_string())
+    }
+
     fn __str__(&self) -> String {
         format!("Token(text='{}', pos={})", self.text, self.position)
     }
@@ -49,4 +198,278 @@ impl PyToken {
             self.text, self.byte_start, self.byte_end, self.position, self.details
         )
     }
+
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> PyResult<(Bound<'py, PyType>, Bound<'py, PyTuple>)> {
+        let py = slf.py();
+        let token = slf.borrow();
+        let args = PyTuple::new(
+            py,
+            [
+                token.text.clone().into_bound_py_any(py)?,
+                token.byte_start.into_bound_py_any(py)?,
+                token.byte_end.into_bound_py_any(py)?,
+                token.position.into_bound_py_any(py)?,
+                token.position_length.into_bound_py_any(py)?,
+                token.details.clone().into_bound_py_any(py)?,
+                token.char_start.into_bound_py_any(py)?,
+                token.char_end.into_bound_py_any(py)?,
+                token.utf16_start.into_bound_py_any(py)?,
+                token.utf16_end.into_bound_py_any(py)?,
+                token.schema().into_bound_py_any(py)?,
+                token.word_id.into_bound_py_any(py)?,
+                token.is_unknown.into_bound_py_any(py)?,
+                token.is_user_dictionary.into_bound_py_any(py)?,
+                token.word_cost.into_bound_py_any(py)?,
+                token.left_context_id.into_bound_py_any(py)?,
+                token.right_context_id.into_bound_py_any(py)?,
+            ],
+        )?;
+
+        Ok((slf.get_type(), args))
+    }
+}
+
+impl PyToken {
+    // Internal helper function to create PyToken from Lindera Token
+    pub fn from_token(
+        token: &mut Token<'_>,
+        index: &TextIndex,
+        schema: &Arc<Schema>,
+        entry: Option<WordEntry>,
+    ) -> Self {
+        PyToken {
+            text: token.text.to_string(),
+            byte_start: token.byte_start,
+            byte_end: token.byte_end,
+            char_start: Some(index.char_offset(token.byte_start)),
+            char_end: Some(index.char_offset(token.byte_end)),
+            utf16_start: index.utf16_offset(token.byte_start),
+            utf16_end: index.utf16_offset(token.byte_end),
+            position: token.position,
+            position_length: token.position_length,
+            details: token.details().iter().map(|d| d.to_string()).collect(),
+            word_id: (!token.word_id.is_unknown()).then_some(token.word_id.id),
+            is_unknown: token.word_id.is_unknown(),
+            is_user_dictionary: !token.word_id.is_unknown() && !token.word_id.is_system(),
+            word_cost: entry.map(|entry| entry.word_cost),
+            left_context_id: entry.map(|entry| entry.left_id),
+            right_context_id: entry.map(|entry| entry.right_id),
+            schema: Some(schema.clone()),
+        }
+    }
+
+    // Internal helper function to create a token of gold data, which spans its surface
+    // and has no dictionary entry
+    pub fn from_surface(
+        text: &str,
+        byte_start: usize,
+        char_start: usize,
+        position: usize,
+        details: Vec<String>,
+        schema: Option<Arc<Schema>>,
+    ) -> Self {
+        PyToken {
+            text: text.to_string(),
+            byte_start,
+            byte_end: byte_start + text.len(),
+            char_start: Some(char_start),
+            char_end: Some(char_start + text.chars().count()),
+            utf16_start: None,
+            utf16_end: None,
+            position,
+            position_length: 1,
+            details,
+            word_id: None,
+            is_unknown: false,
+            is_user_dictionary: false,
+            word_cost: None,
+            left_context_id: None,
+            right_context_id: None,
+            schema,
+        }
+    }
+
+    // Token spanning `text` from `byte_start`, without character offsets or a schema
+    #[cfg(test)]
+    pub fn for_test(text: &str, byte_start: usize, details: Vec<String>) -> Self {
+        PyToken {
+            char_start: None,
+            char_end: None,
+            ..PyToken::from_surface(text, byte_start, 0, 0, details, None)
+        }
+    }
+
+    /// Value of the detail named `field` in the schema, or `None` when the token has none.
+    ///
+    /// `upos` is the value of `Token.upos`, mapped from the part of speech unless the schema has a field of that name.
+    pub fn field_value(&self, field: &str) -> Option<String> {
+        if field == "upos" {
+            return self.upos();
+        }
+        let index = detail_index(self.schema.as_ref()?, field).ok()?;
+        self.details.get(index).cloned()
+    }
+
+    // Value of the first of `fields` found in the schema
+    fn lookup(&self, fields: &[&str]) -> Option<String> {
+        let schema = self.schema.as_ref()?;
+        let index = fields
+            .iter()
+            .find_map(|field| schema.get_field_index(field))?;
+
+        self.details
+            .get(index.checked_sub(COMMON_FIELD_COUNT)?)
+            .cloned()
+    }
+}
+
+// Internal helper function to share the dictionary schema of a segmenter among its tokens
+pub fn dictionary_schema(segmenter: &Segmenter) -> Arc<Schema> {
+    Arc::new(segmenter.dictionary.metadata.dictionary_schema.clone())
+}
+
+/// Dictionary entries of segmented tokens, which hold the costs and context ids that tokens lack.
+///
+/// Entries come from the lattice path, so unknown words have the entry of the unknown dictionary,
+/// and are keyed by the byte span of the token in the segmented text.
+#[derive(Default)]
+pub struct WordEntries {
+    entries: HashMap<(usize, usize), WordEntry>,
+}
+
+impl WordEntries {
+    pub fn insert(&mut self, byte_start: usize, byte_end: usize, entry: WordEntry) {
+        self.entries.insert((byte_start, byte_end), entry);
+    }
+
+    // Entry of a token whose offsets still refer to the segmented text,
+    // or None when a token filter has replaced the word of that span
+    pub fn get(&self, token: &Token<'_>) -> Option<WordEntry> {
+        self.entries
+            .get(&(token.byte_start, token.byte_end))
+            .filter(|entry| entry.word_id == token.word_id)
+            .copied()
+    }
+
+    // Entries of the tokens, resolved before their offsets are mapped back to the original text
+    pub fn for_tokens(&self, tokens: &[Token<'_>]) -> Vec<Option<WordEntry>> {
+        tokens.iter().map(|token| self.get(token)).collect()
+    }
+}
+
+/// Converts UTF-8 byte offsets of a text into character and UTF-16 code unit offsets.
+pub struct TextIndex {
+    // Byte offset of every character followed by the length of the text, or None for ASCII text
+    char_boundaries: Option<Vec<usize>>,
+    // UTF-16 offset of every character followed by the UTF-16 length of the text
+    utf16_offsets: Option<Vec<usize>>,
+    utf16: bool,
+}
+
+impl TextIndex {
+    pub fn new(text: &str, utf16: bool) -> Self {
+        if text.is_ascii() {
+            return TextIndex {
+                char_boundaries: None,
+                utf16_offsets: None,
+                utf16,
+            };
+        }
+
+        let mut char_boundaries: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        char_boundaries.push(text.len());
+
+        let utf16_offsets = utf16.then(|| {
+            let mut offsets = Vec::with_capacity(char_boundaries.len());
+            let mut offset = 0;
+            offsets.push(offset);
+            for c in text.chars() {
+                offset += c.len_utf16();
+                offsets.push(offset);
+            }
+            offsets
+        });
+
+        TextIndex {
+            char_boundaries: Some(char_boundaries),
+            utf16_offsets,
+            utf16,
+        }
+    }
+
+    /// Returns the character offset for a byte offset.
+    pub fn char_offset(&self, byte_offset: usize) -> usize {
+        match &self.char_boundaries {
+            Some(boundaries) => boundaries.partition_point(|&b| b < byte_offset),
+            None => byte_offset,
+        }
+    }
+
+    /// Returns the UTF-16 offset for a byte offset, if UTF-16 offsets were requested.
+    pub fn utf16_offset(&self, byte_offset: usize) -> Option<usize> {
+        if !self.utf16 {
+            return None;
+        }
+        match &self.utf16_offsets {
+            Some(offsets) => offsets.get(self.char_offset(byte_offset)).copied(),
+            None => Some(byte_offset),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_index() {
+        let text = "a東京😀b";
+        let index = TextIndex::new(text, true);
+
+        assert_eq!(index.char_offset(0), 0);
+        assert_eq!(index.char_offset(1), 1);
+        assert_eq!(index.char_offset(7), 3);
+        assert_eq!(index.char_offset(11), 4);
+        assert_eq!(index.char_offset(12), 5);
+
+        assert_eq!(index.utf16_offset(7), Some(3));
+        assert_eq!(index.utf16_offset(11), Some(5));
+        assert_eq!(index.utf16_offset(12), Some(6));
+
+        let index = TextIndex::new(text, false);
+        assert_eq!(index.utf16_offset(7), None);
+    }
+
+    #[test]
+    fn test_new_char_offsets() {
+        let new = |text: &str, byte_start, byte_end, char_start| {
+            let token = PyToken::new(
+                text.to_string(),
+                byte_start,
+                byte_end,
+                0,
+                1,
+                Vec::new(),
+                char_start,
+                None,
+                None,
+                None,
+                None,
+                None,
+                false,
+                false,
+                None,
+                None,
+                None,
+            );
+            (token.char_start, token.char_end)
+        };
+        assert_eq!(new("東京", 0, 6, None), (Some(0), Some(2)));
+        assert_eq!(new("京", 3, 6, None), (None, None));
+        assert_eq!(new("京", 3, 6, Some(1)), (Some(1), Some(2)));
+        // A surface rewritten by a filter does not span its bytes
+        assert_eq!(new("Lindera", 0, 21, None), (Some(0), None));
+    }
 }
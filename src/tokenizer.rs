@@ -1,22 +1,52 @@
-use std::path::Path;
-use std::str::FromStr;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::PyDict;
+use pyo3::types::{PyDict, PyIterator, PyType};
+use pyo3::{PyTraverseError, PyVisit};
+use serde_json::{Value, json};
 
+use lindera::character_filter::OffsetMapping;
+use lindera::dictionary::Schema;
 use lindera::mode::Mode;
-use lindera::tokenizer::{Tokenizer, TokenizerBuilder};
+use lindera::segmenter::Segmenter;
+use lindera::token::Token;
+use lindera_dictionary::viterbi::WordEntry;
 
+use crate::columnar::{StringDtype, TokenColumns};
+use crate::config::{
+    config_to_json, config_to_yaml, default_config, load_config_file, mode_to_value,
+    parse_json_config, parse_yaml_config, read_config_file,
+};
+use crate::filter::{
+    CharacterFilterStage, FilterSpec, FilterTarget, PipelineSpec, TokenFilterStage, insert_filter,
+    remove_filters, set_filter_args,
+};
+use crate::format::{
+    MECAB_EOS_FORMAT, MECAB_NODE_FORMAT, Template, format_conllu, format_tokens, format_wakati,
+};
+use crate::lattice::{segment_nbest, segment_with_entries};
+use crate::mode::{ModeArg, PyMode, PyPenalty};
+use crate::registry::{CHARACTER_FILTERS, TOKEN_FILTERS};
+use crate::schema::detail_index;
 use crate::segmenter::PySegmenter;
-use crate::token::PyToken;
-use crate::util::pydict_to_value;
+use crate::sentence::{DEFAULT_TERMINATORS, PySentence, split_sentences};
+use crate::token::{PyToken, TextIndex, WordEntries, dictionary_schema};
+use crate::util::{parallel_chunks, parallel_map, pydict_to_value, value_to_pydict};
 
 pub type PyDictRef<'a> = &'a Bound<'a, PyDict>;
 
+// Number of texts tokenized at a time into columns, which bounds the memory used besides the columns
+const COLUMNS_CHUNK_SIZE: usize = 1024;
+
 #[pyclass(name = "TokenizerBuilder")]
 pub struct PyTokenizerBuilder {
-    pub inner: TokenizerBuilder,
+    spec: PipelineSpec,
+    utf16_offsets: bool,
 }
 
 #[pymethods]
@@ -24,36 +54,37 @@ impl PyTokenizerBuilder {
     #[new]
     #[pyo3(signature = ())]
     fn new() -> PyResult<Self> {
-        let inner = TokenizerBuilder::new().map_err(|err| {
-            PyValueError::new_err(format!("Failed to create TokenizerBuilder: {err}"))
-        })?;
-
-        Ok(Self { inner })
+        Ok(Self {
+            spec: PipelineSpec::from_config(default_config()?)?,
+            utf16_offsets: false,
+        })
     }
 
+    #[staticmethod]
     #[pyo3(signature = (file_path))]
-    #[allow(clippy::wrong_self_convention)]
-    fn from_file(&self, file_path: &str) -> PyResult<Self> {
-        let inner = TokenizerBuilder::from_file(Path::new(file_path)).map_err(|err| {
-            PyValueError::new_err(format!("Failed to load config from file: {err}"))
-        })?;
-
-        Ok(Self { inner })
+    fn from_file(file_path: &str) -> PyResult<Self> {
+        Ok(Self {
+            spec: PipelineSpec::from_config(load_config_file(Path::new(file_path))?)?,
+            utf16_offsets: false,
+        })
     }
 
-    #[pyo3(signature = (mode))]
-    fn set_mode<'a>(mut slf: PyRefMut<'a, Self>, mode: &str) -> PyResult<PyRefMut<'a, Self>> {
-        let m = Mode::from_str(mode)
-            .map_err(|err| PyValueError::new_err(format!("Failed to create mode: {err}")))?;
+    #[pyo3(signature = (mode, penalty=None))]
+    fn set_mode<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        mode: ModeArg,
+        penalty: Option<PyPenalty>,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        let m = mode.into_mode(penalty)?;
 
-        slf.inner.set_segmenter_mode(&m);
+        slf.spec.segmenter["mode"] = mode_to_value(&m);
 
         Ok(slf)
     }
 
     #[pyo3(signature = (path))]
     fn set_dictionary<'a>(mut slf: PyRefMut<'a, Self>, path: &str) -> PyResult<PyRefMut<'a, Self>> {
-        slf.inner.set_segmenter_dictionary(path);
+        slf.spec.segmenter["dictionary"] = json!(path);
 
         Ok(slf)
     }
@@ -63,102 +94,915 @@ impl PyTokenizerBuilder {
         mut slf: PyRefMut<'a, Self>,
         uri: &str,
     ) -> PyResult<PyRefMut<'a, Self>> {
-        slf.inner.set_segmenter_user_dictionary(uri);
+        slf.spec.segmenter["user_dictionary"] = json!(uri);
+        Ok(slf)
+    }
+
+    #[pyo3(signature = (enabled))]
+    fn set_utf16_offsets<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        enabled: bool,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        slf.utf16_offsets = enabled;
         Ok(slf)
     }
 
     // Character filter and token filter integration
+
+    /// Appends a character filter, either a built-in filter `kind` with its `args`
+    /// or a Python callable that takes and returns a `str`.
     #[pyo3(signature = (kind, args=None))]
     fn append_character_filter<'a>(
         mut slf: PyRefMut<'a, Self>,
-        kind: &str,
+        kind: &Bound<'_, PyAny>,
         args: Option<&Bound<'_, PyDict>>,
     ) -> PyResult<PyRefMut<'a, Self>> {
-        let filter_args = if let Some(dict) = args {
-            pydict_to_value(dict)?
-        } else {
-            serde_json::Value::Object(serde_json::Map::new())
-        };
-
-        slf.inner.append_character_filter(kind, &filter_args);
+        let filter = FilterSpec::extract(kind, args, &CHARACTER_FILTERS)?;
+        slf.spec.character_filters.push(filter);
 
         Ok(slf)
     }
 
+    /// Appends a token filter, either a built-in filter `kind` with its `args`
+    /// or a Python callable that takes and returns a `list[Token]`.
     #[pyo3(signature = (kind, args=None))]
     fn append_token_filter<'a>(
         mut slf: PyRefMut<'a, Self>,
-        kind: &str,
+        kind: &Bound<'_, PyAny>,
         args: Option<&Bound<'_, PyDict>>,
     ) -> PyResult<PyRefMut<'a, Self>> {
-        let filter_args = if let Some(dict) = args {
-            pydict_to_value(dict)?
-        } else {
-            serde_json::Value::Object(serde_json::Map::new())
-        };
+        let filter = FilterSpec::extract(kind, args, &TOKEN_FILTERS)?;
+        slf.spec.token_filters.push(filter);
 
-        slf.inner.append_token_filter(kind, &filter_args);
+        Ok(slf)
+    }
+
+    /// Inserts a character filter before `index`, counting from the end when negative.
+    #[pyo3(signature = (index, kind, args=None))]
+    fn insert_character_filter<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        index: isize,
+        kind: &Bound<'_, PyAny>,
+        args: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        let filter = FilterSpec::extract(kind, args, &CHARACTER_FILTERS)?;
+        insert_filter(&mut slf.spec.character_filters, index, filter);
+
+        Ok(slf)
+    }
+
+    /// Inserts a token filter before `index`, counting from the end when negative.
+    #[pyo3(signature = (index, kind, args=None))]
+    fn insert_token_filter<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        index: isize,
+        kind: &Bound<'_, PyAny>,
+        args: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        let filter = FilterSpec::extract(kind, args, &TOKEN_FILTERS)?;
+        insert_filter(&mut slf.spec.token_filters, index, filter);
+
+        Ok(slf)
+    }
+
+    /// Removes the character filter at an index, or every character filter of a kind.
+    #[pyo3(signature = (target))]
+    fn remove_character_filter<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        target: FilterTarget,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        remove_filters(&mut slf.spec.character_filters, &target, &CHARACTER_FILTERS)?;
+
+        Ok(slf)
+    }
+
+    /// Removes the token filter at an index, or every token filter of a kind.
+    #[pyo3(signature = (target))]
+    fn remove_token_filter<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        target: FilterTarget,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        remove_filters(&mut slf.spec.token_filters, &target, &TOKEN_FILTERS)?;
+
+        Ok(slf)
+    }
+
+    /// Replaces the args of the character filter at an index, or of every character filter of a kind.
+    #[pyo3(signature = (target, args))]
+    fn set_character_filter_args<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        target: FilterTarget,
+        args: &Bound<'_, PyDict>,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        set_filter_args(
+            &mut slf.spec.character_filters,
+            &target,
+            args,
+            &CHARACTER_FILTERS,
+        )?;
+
+        Ok(slf)
+    }
+
+    /// Replaces the args of the token filter at an index, or of every token filter of a kind.
+    #[pyo3(signature = (target, args))]
+    fn set_token_filter_args<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        target: FilterTarget,
+        args: &Bound<'_, PyDict>,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        set_filter_args(&mut slf.spec.token_filters, &target, args, &TOKEN_FILTERS)?;
 
         Ok(slf)
     }
 
+    #[pyo3(signature = ())]
+    fn clear_character_filters<'a>(mut slf: PyRefMut<'a, Self>) -> PyResult<PyRefMut<'a, Self>> {
+        slf.spec.character_filters.clear();
+
+        Ok(slf)
+    }
+
+    #[pyo3(signature = ())]
+    fn clear_token_filters<'a>(mut slf: PyRefMut<'a, Self>) -> PyResult<PyRefMut<'a, Self>> {
+        slf.spec.token_filters.clear();
+
+        Ok(slf)
+    }
+
+    /// Returns the character filters in order, as `{"kind": ..., "args": ...}` dicts
+    /// or the callables of Python filters.
+    #[pyo3(signature = ())]
+    fn character_filters(&self, py: Python<'_>) -> PyResult<Vec<Py<PyAny>>> {
+        filter_objects(py, &self.spec.character_filters)
+    }
+
+    /// Returns the token filters in order, as `{"kind": ..., "args": ...}` dicts
+    /// or the callables of Python filters.
+    #[pyo3(signature = ())]
+    fn token_filters(&self, py: Python<'_>) -> PyResult<Vec<Py<PyAny>>> {
+        filter_objects(py, &self.spec.token_filters)
+    }
+
     #[pyo3(signature = ())]
     fn build(&self) -> PyResult<PyTokenizer> {
-        let tokenizer = self
-            .inner
-            .build()
-            .map_err(|err| PyValueError::new_err(format!("Failed to build tokenizer: {err}")))?;
+        PyTokenizer::from_spec(self.spec.clone(), self.utf16_offsets)
+    }
+
+    /// Returns the configuration assembled so far as a dict in the shape of `lindera.yml`.
+    #[pyo3(signature = ())]
+    fn to_config(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        value_to_pydict(py, &self.spec.to_config()?)
+    }
+
+    #[pyo3(signature = ())]
+    fn to_yaml(&self) -> PyResult<String> {
+        config_to_yaml(&self.spec.to_config()?)
+    }
 
-        Ok(PyTokenizer { inner: tokenizer })
+    #[pyo3(signature = (indent=None))]
+    fn to_json(&self, indent: Option<usize>) -> PyResult<String> {
+        config_to_json(&self.spec.to_config()?, indent)
+    }
+
+    fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        self.spec.traverse(&visit)
+    }
+
+    fn __clear__(&mut self) {
+        self.spec.clear_python_filters();
     }
 }
 
-#[pyclass(name = "Tokenizer")]
+/// Tokenizer that applies character filters, segmentation and token filters.
+///
+/// The GIL is released while text is being analyzed, and a single `Tokenizer`
+/// can safely be shared and used concurrently by multiple Python threads.
+/// Python callable filters take the GIL back while they run.
+#[pyclass(name = "Tokenizer", module = "lindera")]
 pub struct PyTokenizer {
-    inner: Tokenizer,
+    segmenter: Segmenter,
+    // Dictionary schema shared by the tokens
+    pub schema: Arc<Schema>,
+    character_filters: Vec<CharacterFilterStage>,
+    token_filters: Vec<TokenFilterStage>,
+    // Pipeline the tokenizer was built from
+    spec: PipelineSpec,
+    // Whether to compute UTF-16 code unit offsets of tokens
+    utf16_offsets: bool,
 }
 
 #[pymethods]
 impl PyTokenizer {
     #[new]
-    #[pyo3(signature = (segmenter))]
-    fn new(segmenter: PySegmenter) -> PyResult<Self> {
+    #[pyo3(signature = (segmenter, utf16_offsets=false))]
+    fn new(segmenter: PySegmenter, utf16_offsets: bool) -> PyResult<Self> {
         Ok(Self {
-            inner: Tokenizer::new(segmenter.inner),
+            segmenter: segmenter.inner,
+            schema: segmenter.schema,
+            character_filters: Vec::new(),
+            token_filters: Vec::new(),
+            spec: PipelineSpec {
+                segmenter: segmenter.config,
+                character_filters: Vec::new(),
+                token_filters: Vec::new(),
+            },
+            utf16_offsets,
         })
     }
 
-    #[pyo3(signature = (config))]
-    #[allow(clippy::wrong_self_convention)]
-    fn from_config(&self, config: &Bound<'_, PyDict>) -> PyResult<Self> {
-        let config_value = pydict_to_value(config)?;
-        let tokenizer = Tokenizer::from_config(&config_value)
-            .map_err(|err| PyValueError::new_err(format!("Failed to create tokenizer: {err}")))?;
+    #[staticmethod]
+    #[pyo3(signature = (config, utf16_offsets=false))]
+    fn from_config(config: &Bound<'_, PyDict>, utf16_offsets: bool) -> PyResult<Self> {
+        Self::from_value(pydict_to_value(config)?, utf16_offsets)
+    }
+
+    /// Creates a tokenizer from a dict in the shape of `lindera.yml`, the same as `from_config`.
+    #[staticmethod]
+    #[pyo3(signature = (config, utf16_offsets=false))]
+    fn from_dict(config: &Bound<'_, PyDict>, utf16_offsets: bool) -> PyResult<Self> {
+        Self::from_config(config, utf16_offsets)
+    }
+
+    /// Creates a tokenizer from a YAML string in the shape of `lindera.yml`.
+    #[staticmethod]
+    #[pyo3(signature = (yaml, utf16_offsets=false))]
+    fn from_yaml(yaml: &str, utf16_offsets: bool) -> PyResult<Self> {
+        Self::from_value(parse_yaml_config(yaml)?, utf16_offsets)
+    }
+
+    /// Creates a tokenizer from a JSON string in the shape of `lindera.yml`.
+    #[staticmethod]
+    #[pyo3(signature = (json, utf16_offsets=false))]
+    fn from_json(json: &str, utf16_offsets: bool) -> PyResult<Self> {
+        Self::from_value(parse_json_config(json)?, utf16_offsets)
+    }
+
+    /// Creates a tokenizer from a YAML or JSON config file such as `lindera.yml`.
+    #[staticmethod]
+    #[pyo3(signature = (path, utf16_offsets=false))]
+    fn from_path(path: PathBuf, utf16_offsets: bool) -> PyResult<Self> {
+        Self::from_value(read_config_file(&path)?, utf16_offsets)
+    }
+
+    #[getter]
+    fn utf16_offsets(&self) -> bool {
+        self.utf16_offsets
+    }
+
+    #[getter]
+    fn mode(&self) -> PyMode {
+        self.segmenter.mode.clone().into()
+    }
+
+    /// Penalty of the decompose mode, or `None` in normal mode.
+    #[getter]
+    fn penalty(&self) -> Option<PyPenalty> {
+        match &self.segmenter.mode {
+            Mode::Normal => None,
+            Mode::Decompose(penalty) => Some(penalty.clone().into()),
+        }
+    }
+
+    /// Returns the configuration of the tokenizer as a dict in the shape of `lindera.yml`.
+    ///
+    /// The configuration can be saved and passed to `from_config` to rebuild an identical tokenizer.
+    #[pyo3(signature = ())]
+    fn to_config(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        value_to_pydict(py, &self.spec.to_config()?)
+    }
+
+    #[pyo3(signature = ())]
+    fn to_yaml(&self) -> PyResult<String> {
+        config_to_yaml(&self.spec.to_config()?)
+    }
+
+    #[pyo3(signature = (indent=None))]
+    fn to_json(&self, indent: Option<usize>) -> PyResult<String> {
+        config_to_json(&self.spec.to_config()?, indent)
+    }
+
+    // The tokenizer is rebuilt from its pipeline when unpickled.
+    // Python callable filters are pickled along with it.
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> PyResult<(
+        Bound<'py, PyAny>,
+        (Py<PyAny>, Vec<Py<PyAny>>, Vec<Py<PyAny>>, bool),
+    )> {
+        let py = slf.py();
+        let tokenizer = slf.borrow();
+        let spec = &tokenizer.spec;
+        Ok((
+            slf.get_type().getattr("_from_state")?,
+            (
+                value_to_pydict(py, &spec.segmenter)?,
+                filter_objects(py, &spec.character_filters)?,
+                filter_objects(py, &spec.token_filters)?,
+                tokenizer.utf16_offsets,
+            ),
+        ))
+    }
+
+    #[classmethod]
+    fn _from_state(
+        _cls: &Bound<'_, PyType>,
+        segmenter: &Bound<'_, PyDict>,
+        character_filters: Vec<Bound<'_, PyAny>>,
+        token_filters: Vec<Bound<'_, PyAny>>,
+        utf16_offsets: bool,
+    ) -> PyResult<Self> {
+        let spec = PipelineSpec {
+            segmenter: pydict_to_value(segmenter)?,
+            character_filters: character_filters
+                .iter()
+                .map(|filter| FilterSpec::from_object(filter, &CHARACTER_FILTERS))
+                .collect::<PyResult<_>>()?,
+            token_filters: token_filters
+                .iter()
+                .map(|filter| FilterSpec::from_object(filter, &TOKEN_FILTERS))
+                .collect::<PyResult<_>>()?,
+        };
+
+        Self::from_spec(spec, utf16_offsets)
+    }
+
+    #[pyo3(signature = (text))]
+    fn tokenize(&self, py: Python<'_>, text: String) -> PyResult<Vec<PyToken>> {
+        // Run the tokenizer without holding the GIL
+        py.detach(|| self.tokenize_text(&text))
+    }
 
-        Ok(Self { inner: tokenizer })
+    /// Formats the tokens of a text as MeCab does, one `surface\tfeatures` line per token
+    /// and `EOS` after every line of the text.
+    ///
+    /// `node_format` and `eos_format` replace the output for each token and the end of each line
+    /// as the `-F` and `-E` options of MeCab. Features can be referred to by index, as in `%f[7]`,
+    /// or by the field names of the dictionary schema, as in `%f[reading]`.
+    /// As in MeCab, `%ps` and `%pe` are byte offsets within the line.
+    #[pyo3(signature = (text, node_format=None, eos_format=None))]
+    fn format_mecab(
+        &self,
+        py: Python<'_>,
+        text: String,
+        node_format: Option<&str>,
+        eos_format: Option<&str>,
+    ) -> PyResult<String> {
+        let template = Template::parse(
+            node_format.unwrap_or(MECAB_NODE_FORMAT),
+            &self.segmenter.dictionary.metadata.dictionary_schema,
+        )
+        .map_err(|err| PyValueError::new_err(format!("Failed to parse node format: {err}")))?;
+        let eos_format = eos_format.unwrap_or(MECAB_EOS_FORMAT);
+
+        // MeCab analyzes every line of its input as a sentence of its own
+        py.detach(|| {
+            let mut output = String::new();
+            for line in text.lines() {
+                let tokens = self.tokenize_text(line)?;
+                output.push_str(&format_tokens(&tokens, &template, eos_format));
+            }
+            Ok(output)
+        })
     }
 
+    /// Returns the surfaces of the tokens of a text separated by spaces, one output line per line of the text,
+    /// as the wakati output of MeCab.
     #[pyo3(signature = (text))]
-    fn tokenize(&self, text: &str) -> PyResult<Vec<PyToken>> {
-        // Tokenize the processed text
-        let mut tokens = self
-            .inner
-            .tokenize(text)
-            .map_err(|err| PyValueError::new_err(format!("Failed to tokenize text: {err}")))?;
-
-        // Convert to PyToken
-        let py_tokens: Vec<PyToken> = tokens
-            .iter_mut()
-            .map(|t| PyToken {
-                text: t.text.to_string(),
-                byte_start: t.byte_start,
-                byte_end: t.byte_end,
-                position: t.position,
-                position_length: t.position_length,
-                details: t.details().iter().map(|d| d.to_string()).collect(),
+    fn format_wakati(&self, py: Python<'_>, text: String) -> PyResult<String> {
+        py.detach(|| {
+            let mut output = String::new();
+            for line in text.lines() {
+                output.push_str(&format_wakati(&self.tokenize_text(line)?));
+            }
+            Ok(output)
+        })
+    }
+
+    /// Splits a text into sentences.
+    ///
+    /// A sentence ends after one of `terminators` (`。．！？!?` by default), keeping the terminators,
+    /// closing brackets and ellipses that directly follow it, or at a line break when `split_on_newlines` is set.
+    /// Terminators inside brackets such as 「」 do not end a sentence.
+    #[pyo3(signature = (text, terminators=None, split_on_newlines=true))]
+    fn split_sentences(
+        &self,
+        text: &str,
+        terminators: Option<Vec<String>>,
+        split_on_newlines: bool,
+    ) -> Vec<PySentence> {
+        let index = TextIndex::new(text, self.utf16_offsets);
+        sentence_ranges(text, terminators.as_deref(), split_on_newlines)
+            .into_iter()
+            .map(|range| PySentence::from_range(text, range, &index))
+            .collect()
+    }
+
+    /// Splits a text into sentences as `split_sentences` does and tokenizes each of them.
+    ///
+    /// Token offsets refer to the whole text, while positions start over in every sentence.
+    #[pyo3(signature = (text, terminators=None, split_on_newlines=true))]
+    fn tokenize_sentences(
+        &self,
+        py: Python<'_>,
+        text: String,
+        terminators: Option<Vec<String>>,
+        split_on_newlines: bool,
+    ) -> PyResult<Vec<Vec<PyToken>>> {
+        py.detach(|| {
+            let sentences =
+                self.tokenize_sentences_text(&text, terminators.as_deref(), split_on_newlines)?;
+            Ok(sentences.into_iter().map(|(_, tokens)| tokens).collect())
+        })
+    }
+
+    /// Tokenizes a text sentence by sentence and formats it as CoNLL-U.
+    ///
+    /// Sentences are split as in `split_sentences`. FORM is the surface, LEMMA the base form,
+    /// UPOS is mapped from the part of speech as `Token.upos` and XPOS joins the part-of-speech fields
+    /// of the dictionary schema. MISC holds `SpaceAfter=No` and the character range of each token.
+    /// The dependency columns are left empty.
+    #[pyo3(signature = (text, terminators=None, split_on_newlines=true))]
+    fn to_conllu(
+        &self,
+        py: Python<'_>,
+        text: String,
+        terminators: Option<Vec<String>>,
+        split_on_newlines: bool,
+    ) -> PyResult<String> {
+        py.detach(|| {
+            let sentences =
+                self.tokenize_sentences_text(&text, terminators.as_deref(), split_on_newlines)?;
+            Ok(format_conllu(&text, &sentences))
+        })
+    }
+
+    #[pyo3(signature = (texts))]
+    fn tokenize_batch(&self, py: Python<'_>, texts: Vec<String>) -> PyResult<Vec<Vec<PyToken>>> {
+        // Tokenize the texts in parallel, keeping the input order
+        py.detach(|| {
+            parallel_map(&texts, |text| self.tokenize_text(text))
+                .into_iter()
+                .collect()
+        })
+    }
+
+    /// Tokenizes a list of texts into a `pyarrow.RecordBatch` with one row per token.
+    ///
+    /// The columns are `doc_id` (the index of the text), `surface`, `byte_start`, `byte_end`, `position`
+    /// and one column per field of `fields`, named after the dictionary schema.
+    /// The columns are built in Rust, without creating a `Token` object per token.
+    #[pyo3(signature = (texts, fields=None))]
+    fn tokenize_to_arrow<'py>(
+        &self,
+        py: Python<'py>,
+        texts: Vec<String>,
+        fields: Option<Vec<String>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.tokenize_columns(py, texts, fields)?.to_arrow(py)
+    }
+
+    /// Tokenizes a list of texts into a dict of NumPy arrays with the columns of `tokenize_to_arrow`.
+    ///
+    /// With `string_dtype="unicode"`, string columns are fixed width `<U` arrays as wide as their longest value,
+    /// so a single long token widens every row. `string_dtype="object"` gives arrays of Python `str` instead.
+    #[pyo3(signature = (texts, fields=None, string_dtype="unicode"))]
+    fn tokenize_to_numpy<'py>(
+        &self,
+        py: Python<'py>,
+        texts: Vec<String>,
+        fields: Option<Vec<String>>,
+        string_dtype: &str,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let string_dtype = StringDtype::from_name(string_dtype)
+            .map_err(|err| PyValueError::new_err(format!("Failed to tokenize to NumPy: {err}")))?;
+        self.tokenize_columns(py, texts, fields)?
+            .to_numpy(py, string_dtype)
+    }
+
+    /// Tokenizes a text on a background thread and returns an awaitable asyncio future.
+    ///
+    /// The work runs in `executor`, or in the default executor of the running event loop
+    /// when it is omitted. The GIL is released during tokenization, so the event loop is not blocked.
+    #[pyo3(signature = (text, executor=None))]
+    fn tokenize_async<'py>(
+        slf: &Bound<'py, Self>,
+        text: String,
+        executor: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        run_in_executor(slf, "tokenize", text, executor)
+    }
+
+    /// Tokenizes a list of texts in parallel on a background thread and returns an awaitable asyncio future.
+    #[pyo3(signature = (texts, executor=None))]
+    fn tokenize_batch_async<'py>(
+        slf: &Bound<'py, Self>,
+        texts: Vec<String>,
+        executor: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        run_in_executor(slf, "tokenize_batch", texts, executor)
+    }
+
+    /// Lazily tokenizes every string of an iterable, such as an open text file.
+    ///
+    /// Only `batch_size` texts are read from the iterable at a time and tokenized in parallel,
+    /// so memory stays bounded regardless of the size of the input.
+    /// A trailing line break is removed from each text before tokenization.
+    #[pyo3(signature = (iterable, batch_size=1))]
+    fn tokenize_iter(
+        slf: Bound<'_, Self>,
+        iterable: &Bound<'_, PyAny>,
+        batch_size: usize,
+    ) -> PyResult<PyTokenIterator> {
+        if batch_size == 0 {
+            return Err(PyValueError::new_err("batch_size must be greater than 0"));
+        }
+
+        Ok(PyTokenIterator {
+            tokenizer: slf.unbind(),
+            iterator: iterable.try_iter()?.unbind(),
+            batch_size,
+            buffer: VecDeque::new(),
+        })
+    }
+
+    #[pyo3(signature = (text, n))]
+    fn tokenize_nbest(
+        &self,
+        py: Python<'_>,
+        text: String,
+        n: usize,
+    ) -> PyResult<Vec<(Vec<PyToken>, i32)>> {
+        if n == 0 {
+            return Err(PyValueError::new_err("n must be greater than 0"));
+        }
+
+        py.detach(|| self.tokenize_nbest_text(&text, n))
+    }
+
+    // Python filters may refer back to the tokenizer, so their callables take part in garbage collection
+    fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+        self.spec.traverse(&visit)?;
+        for filter in &self.character_filters {
+            filter.traverse(&visit)?;
+        }
+        for filter in &self.token_filters {
+            filter.traverse(&visit)?;
+        }
+
+        Ok(())
+    }
+
+    fn __clear__(&mut self) {
+        self.spec.clear_python_filters();
+        self.character_filters.retain(|filter| !filter.is_python());
+        self.token_filters.retain(|filter| !filter.is_python());
+    }
+}
+
+impl PyTokenizer {
+    // Internal helper function to create PyTokenizer from a configuration value
+    fn from_value(config: Value, utf16_offsets: bool) -> PyResult<Self> {
+        Self::from_spec(PipelineSpec::from_config(config)?, utf16_offsets)
+    }
+
+    // Internal helper function to load the segmenter and the filters of a pipeline
+    fn from_spec(spec: PipelineSpec, utf16_offsets: bool) -> PyResult<Self> {
+        let segmenter = Segmenter::from_config(&spec.segmenter)
+            .map_err(|err| PyValueError::new_err(format!("Failed to create tokenizer: {err}")))?;
+        let character_filters = spec
+            .character_filters
+            .iter()
+            .map(CharacterFilterStage::load)
+            .collect::<PyResult<_>>()?;
+        let token_filters = spec
+            .token_filters
+            .iter()
+            .map(TokenFilterStage::load)
+            .collect::<PyResult<_>>()?;
+
+        Ok(Self {
+            schema: dictionary_schema(&segmenter),
+            segmenter,
+            character_filters,
+            token_filters,
+            spec,
+            utf16_offsets,
+        })
+    }
+
+    // Run the character filters, the segmenter and the token filters,
+    // then map the token offsets back to the original text
+    pub fn tokenize_text(&self, text: &str) -> PyResult<Vec<PyToken>> {
+        let index = TextIndex::new(text, self.utf16_offsets);
+        self.analyze(text, |tokens, entries| {
+            tokens
+                .iter_mut()
+                .zip(entries)
+                .map(|(token, entry)| PyToken::from_token(token, &index, &self.schema, *entry))
+                .collect()
+        })
+    }
+
+    // Run the whole pipeline on a text and pass the resulting Lindera tokens to `f`,
+    // with offsets already mapped back to the original text, along with their dictionary entries
+    fn analyze<R>(
+        &self,
+        text: &str,
+        f: impl FnOnce(&mut [Token<'_>], &[Option<WordEntry>]) -> R,
+    ) -> PyResult<R> {
+        let (normalized_text, offset_mappings) = self.apply_character_filters(text)?;
+
+        let (mut tokens, mut entries) = segment_with_entries(&self.segmenter, &normalized_text);
+        self.apply_token_filters(&mut tokens, &normalized_text, &mut entries)?;
+        let entries = entries.for_tokens(&tokens);
+        correct_offsets(&mut tokens, &offset_mappings);
+
+        Ok(f(&mut tokens, &entries))
+    }
+
+    // Tokenize every sentence of a text, with token offsets referring to the whole text
+    fn tokenize_sentences_text(
+        &self,
+        text: &str,
+        terminators: Option<&[String]>,
+        split_on_newlines: bool,
+    ) -> PyResult<Vec<(PySentence, Vec<PyToken>)>> {
+        let index = TextIndex::new(text, self.utf16_offsets);
+        sentence_ranges(text, terminators, split_on_newlines)
+            .into_iter()
+            .map(|range| {
+                let sentence = PySentence::from_range(text, range, &index);
+                let mut tokens = self.tokenize_text(&sentence.text)?;
+                for token in &mut tokens {
+                    token.byte_start += sentence.byte_start;
+                    token.byte_end += sentence.byte_start;
+                    token.char_start = token.char_start.map(|c| c + sentence.char_start);
+                    token.char_end = token.char_end.map(|c| c + sentence.char_start);
+                    token.utf16_start = token
+                        .utf16_start
+                        .zip(sentence.utf16_start)
+                        .map(|(a, b)| a + b);
+                    token.utf16_end = token
+                        .utf16_end
+                        .zip(sentence.utf16_start)
+                        .map(|(a, b)| a + b);
+                }
+                Ok((sentence, tokens))
             })
-            .collect();
+            .collect()
+    }
+
+    fn tokenize_columns(
+        &self,
+        py: Python<'_>,
+        texts: Vec<String>,
+        fields: Option<Vec<String>>,
+    ) -> PyResult<TokenColumns> {
+        let schema = &self.segmenter.dictionary.metadata.dictionary_schema;
+        let fields = fields
+            .unwrap_or_default()
+            .into_iter()
+            .map(|field| {
+                let index = detail_index(schema, &field).map_err(|err| {
+                    PyValueError::new_err(format!("Failed to select field: {err}"))
+                })?;
+                Ok((field, index))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        // Texts are tokenized a chunk at a time, straight into columns that hold only the selected fields
+        py.detach(|| {
+            let mut columns = TokenColumns::new(fields);
+            for chunk in texts.chunks(COLUMNS_CHUNK_SIZE) {
+                let parts = parallel_chunks(chunk, |texts| {
+                    let mut part = columns.empty();
+                    for text in texts {
+                        self.analyze(text, |tokens, _| part.push(tokens))?;
+                    }
+                    Ok::<_, PyErr>(part)
+                });
+                for part in parts {
+                    columns.append(part?);
+                }
+            }
+
+            Ok(columns)
+        })
+    }
+
+    fn tokenize_nbest_text(&self, text: &str, n: usize) -> PyResult<Vec<(Vec<PyToken>, i32)>> {
+        let (normalized_text, offset_mappings) = self.apply_character_filters(text)?;
+        let index = TextIndex::new(text, self.utf16_offsets);
+
+        segment_nbest(&self.segmenter, &normalized_text, n)
+            .into_iter()
+            .map(|(mut tokens, mut entries, cost)| {
+                self.apply_token_filters(&mut tokens, &normalized_text, &mut entries)?;
+                let entries = entries.for_tokens(&tokens);
+                correct_offsets(&mut tokens, &offset_mappings);
+
+                Ok((
+                    tokens
+                        .iter_mut()
+                        .zip(entries)
+                        .map(|(token, entry)| {
+                            PyToken::from_token(token, &index, &self.schema, entry)
+                        })
+                        .collect(),
+                    cost,
+                ))
+            })
+            .collect()
+    }
+
+    // Apply the character filters in order, keeping the offset mapping of each filter
+    fn apply_character_filters<'a>(
+        &self,
+        text: &'a str,
+    ) -> PyResult<(Cow<'a, str>, Vec<OffsetMapping>)> {
+        let mut normalized_text = Cow::Borrowed(text);
+        let mut offset_mappings = Vec::with_capacity(self.character_filters.len());
+
+        for character_filter in &self.character_filters {
+            let mapping = character_filter.apply(normalized_text.to_mut())?;
+            if !mapping.is_empty() {
+                offset_mappings.push(mapping);
+            }
+        }
+
+        Ok((normalized_text, offset_mappings))
+    }
+
+    fn apply_token_filters<'a>(
+        &'a self,
+        tokens: &mut Vec<Token<'a>>,
+        text: &str,
+        entries: &mut WordEntries,
+    ) -> PyResult<()> {
+        for token_filter in &self.token_filters {
+            token_filter.apply(tokens, text, &self.segmenter, &self.schema, entries)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[pyclass(name = "TokenIterator")]
+pub struct PyTokenIterator {
+    tokenizer: Py<PyTokenizer>,
+    iterator: Py<PyIterator>,
+    batch_size: usize,
+    buffer: VecDeque<Vec<PyToken>>,
+}
+
+#[pymethods]
+impl PyTokenIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<Option<Vec<PyToken>>> {
+        if slf.buffer.is_empty() {
+            // Read the next batch of texts from the underlying iterator
+            let mut texts = Vec::with_capacity(slf.batch_size);
+            let mut iterator = slf.iterator.bind(py).clone();
+            for item in iterator.by_ref().take(slf.batch_size) {
+                let mut text: String = item?.extract()?;
+                if text.ends_with('\n') {
+                    text.pop();
+                    if text.ends_with('\r') {
+                        text.pop();
+                    }
+                }
+                texts.push(text);
+            }
+
+            let tokenizer_ref = slf.tokenizer.borrow(py);
+            let tokenizer: &PyTokenizer = &tokenizer_ref;
+            let results = py.detach(|| parallel_map(&texts, |text| tokenizer.tokenize_text(text)));
+            drop(tokenizer_ref);
+
+            for result in results {
+                slf.buffer.push_back(result?);
+            }
+        }
+
+        Ok(slf.buffer.pop_front())
+    }
+}
+
+fn sentence_ranges(
+    text: &str,
+    terminators: Option<&[String]>,
+    split_on_newlines: bool,
+) -> Vec<Range<usize>> {
+    match terminators {
+        Some(terminators) => split_sentences(text, terminators, split_on_newlines),
+        None => split_sentences(text, DEFAULT_TERMINATORS, split_on_newlines),
+    }
+}
+
+fn filter_objects(py: Python<'_>, filters: &[FilterSpec]) -> PyResult<Vec<Py<PyAny>>> {
+    filters.iter().map(|filter| filter.to_object(py)).collect()
+}
+
+// Schedule a method of the tokenizer on an executor of the running asyncio event loop
+fn run_in_executor<'py, A>(
+    tokenizer: &Bound<'py, PyTokenizer>,
+    method: &str,
+    arg: A,
+    executor: Option<Bound<'py, PyAny>>,
+) -> PyResult<Bound<'py, PyAny>>
+where
+    A: IntoPyObject<'py>,
+{
+    let py = tokenizer.py();
+    let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+    let func = tokenizer.getattr(method)?;
+
+    event_loop.call_method1("run_in_executor", (executor, func, arg))
+}
+
+// Correct token offsets back to the original text, undoing the character filters in reverse order
+fn correct_offsets(tokens: &mut [Token<'_>], offset_mappings: &[OffsetMapping]) {
+    if offset_mappings.is_empty() {
+        return;
+    }
+
+    for token in tokens.iter_mut() {
+        for mapping in offset_mappings.iter().rev() {
+            token.byte_start = correct_offset(mapping, token.byte_start, false);
+            token.byte_end = correct_offset(mapping, token.byte_end, true);
+        }
+    }
+}
+
+// Map a byte offset in the output of a character filter to the input of that filter.
+//
+// Unlike `OffsetMapping::correct_offset`, an offset that falls inside a replaced span is never
+// interpolated: a start offset snaps to the start of the original span and an end offset to its end,
+// so that the corrected span always covers whole characters of the original text.
+fn correct_offset(mapping: &OffsetMapping, offset: usize, is_end: bool) -> usize {
+    let mut delta: isize = 0;
+
+    for transformation in &mapping.transformations {
+        if offset < transformation.filtered_start {
+            break;
+        }
+
+        let is_deletion = transformation.filtered_start == transformation.filtered_end;
+        if offset == transformation.filtered_start && (is_end || !is_deletion) {
+            return transformation.original_start;
+        }
+        if offset < transformation.filtered_end {
+            return if is_end {
+                transformation.original_end
+            } else {
+                transformation.original_start
+            };
+        }
+
+        delta += (transformation.original_end - transformation.original_start) as isize
+            - (transformation.filtered_end - transformation.filtered_start) as isize;
+    }
+
+    (offset as isize + delta) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use lindera::character_filter::Transformation;
+
+    use super::*;
+
+    #[test]
+    fn test_correct_offset() {
+        // "１０㍑" -> "10リットル"
+        let mapping = OffsetMapping::with_transformations(vec![
+            Transformation::new(0, 3, 0, 1),
+            Transformation::new(3, 6, 1, 2),
+            Transformation::new(6, 9, 2, 14),
+        ]);
+
+        assert_eq!(correct_offset(&mapping, 0, false), 0);
+        assert_eq!(correct_offset(&mapping, 2, true), 6);
+        assert_eq!(correct_offset(&mapping, 2, false), 6);
+        assert_eq!(correct_offset(&mapping, 14, true), 9);
+
+        // "リット" ends inside the replacement of "㍑"
+        assert_eq!(correct_offset(&mapping, 11, true), 9);
+        assert_eq!(correct_offset(&mapping, 11, false), 6);
+    }
+
+    #[test]
+    fn test_correct_offset_with_deletion() {
+        // "a--b" -> "ab"
+        let mapping = OffsetMapping::with_transformations(vec![Transformation::new(1, 3, 1, 1)]);
 
-        Ok(py_tokens)
+        assert_eq!(correct_offset(&mapping, 1, true), 1);
+        assert_eq!(correct_offset(&mapping, 1, false), 3);
+        assert_eq!(correct_offset(&mapping, 2, true), 4);
     }
 }
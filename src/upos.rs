@@ -0,0 +1,238 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use lindera::dictionary::Schema;
+
+/// Part-of-speech tag set of a dictionary, which decides how its details map onto UPOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PosTagSet {
+    Ipadic,
+    Unidic,
+    KoDic,
+    CcCedict,
+}
+
+impl PosTagSet {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "ipadic" | "ipadic-neologd" => Ok(PosTagSet::Ipadic),
+            "unidic" => Ok(PosTagSet::Unidic),
+            "ko-dic" => Ok(PosTagSet::KoDic),
+            "cc-cedict" => Ok(PosTagSet::CcCedict),
+            _ => Err(format!(
+                "unknown dictionary kind '{name}', expected one of ipadic, ipadic-neologd, unidic, ko-dic, cc-cedict"
+            )),
+        }
+    }
+
+    // Recognize the tag set from the fields that are specific to each dictionary schema
+    pub fn from_schema(schema: &Schema) -> Option<Self> {
+        let has = |field: &str| schema.get_field_index(field).is_some();
+        if has("part_of_speech_tag") {
+            Some(PosTagSet::KoDic)
+        } else if has("pinyin") {
+            Some(PosTagSet::CcCedict)
+        } else if has("lexeme") || has("orthographic_base_form") {
+            Some(PosTagSet::Unidic)
+        } else if has("part_of_speech") {
+            Some(PosTagSet::Ipadic)
+        } else {
+            None
+        }
+    }
+}
+
+/// Maps the part-of-speech details of a token onto a Universal Dependencies UPOS tag.
+///
+/// Unknown words, whose details are `UNK`, and tags without a counterpart map to `X`.
+pub fn to_upos_tag<S: AsRef<str>>(tag_set: PosTagSet, details: &[S]) -> &'static str {
+    let field = |index: usize| details.get(index).map_or("*", AsRef::as_ref);
+    match tag_set {
+        PosTagSet::Ipadic => ipadic_upos(field(0), field(1)),
+        PosTagSet::Unidic => unidic_upos(field(0), field(1)),
+        // Inflected forms are tagged as a sequence such as `VV+EP`, the first tag is the head
+        PosTagSet::KoDic => ko_dic_upos(field(0).split('+').next().unwrap_or_default()),
+        PosTagSet::CcCedict => cc_cedict_upos(field(0)),
+    }
+}
+
+fn ipadic_upos(pos: &str, sub: &str) -> &'static str {
+    match (pos, sub) {
+        ("名詞", "固有名詞") => "PROPN",
+        ("名詞", "代名詞") => "PRON",
+        ("名詞", "数") => "NUM",
+        ("名詞", "形容動詞語幹" | "ナイ形容詞語幹") => "ADJ",
+        ("名詞", _) => "NOUN",
+        ("動詞", "非自立") => "AUX",
+        ("動詞", _) => "VERB",
+        ("形容詞", _) => "ADJ",
+        ("副詞", _) => "ADV",
+        ("連体詞", _) => "DET",
+        ("接続詞", _) => "CCONJ",
+        ("感動詞" | "フィラー", _) => "INTJ",
+        ("助動詞", _) => "AUX",
+        ("助詞", "接続助詞") => "SCONJ",
+        ("助詞", "並立助詞") => "CCONJ",
+        ("助詞", "終助詞" | "間投助詞" | "副助詞／並立助詞／終助詞") => "PART",
+        ("助詞", _) => "ADP",
+        ("接頭詞", _) => "NOUN",
+        ("記号", "句点" | "読点" | "括弧開" | "括弧閉") => "PUNCT",
+        ("記号", _) => "SYM",
+        ("その他", "間投") => "INTJ",
+        _ => "X",
+    }
+}
+
+fn unidic_upos(pos: &str, sub: &str) -> &'static str {
+    match (pos, sub) {
+        ("名詞", "固有名詞") => "PROPN",
+        ("名詞", "数詞") => "NUM",
+        ("名詞", "助動詞語幹") => "AUX",
+        ("名詞", _) => "NOUN",
+        ("代名詞", _) => "PRON",
+        ("形状詞", "助動詞語幹") => "AUX",
+        ("形状詞", _) => "ADJ",
+        ("連体詞", _) => "DET",
+        ("副詞", _) => "ADV",
+        ("接続詞", _) => "CCONJ",
+        ("感動詞", _) => "INTJ",
+        ("動詞", _) => "VERB",
+        ("形容詞", _) => "ADJ",
+        ("助動詞", _) => "AUX",
+        ("助詞", "接続助詞" | "準体助詞") => "SCONJ",
+        ("助詞", "終助詞") => "PART",
+        ("助詞", _) => "ADP",
+        ("接頭辞", _) => "NOUN",
+        ("接尾辞", "名詞的") => "NOUN",
+        ("接尾辞", _) => "PART",
+        ("補助記号", _) => "PUNCT",
+        ("記号" | "空白", _) => "SYM",
+        _ => "X",
+    }
+}
+
+// Sejong tag set
+fn ko_dic_upos(tag: &str) -> &'static str {
+    match tag {
+        "NNG" | "NNB" | "NNBC" | "XPN" | "XSN" | "XR" => "NOUN",
+        "NNP" => "PROPN",
+        "NP" => "PRON",
+        "NR" | "SN" => "NUM",
+        "VV" | "XSV" => "VERB",
+        "VA" | "VCN" | "XSA" => "ADJ",
+        "VX" | "VCP" => "AUX",
+        "MM" => "DET",
+        "MAG" => "ADV",
+        "MAJ" | "JC" => "CCONJ",
+        "IC" => "INTJ",
+        "JKS" | "JKC" | "JKG" | "JKO" | "JKB" | "JKV" | "JKQ" | "JX" => "ADP",
+        "EP" | "EF" | "EC" | "ETN" | "ETM" => "PART",
+        "SF" | "SE" | "SSO" | "SSC" | "SC" => "PUNCT",
+        "SY" => "SYM",
+        _ => "X",
+    }
+}
+
+// The CC-CEDICT entries themselves leave the part of speech as `*`, which maps to X,
+// so only words of a user dictionary carry one, named in Chinese or with a PKU tag
+fn cc_cedict_upos(pos: &str) -> &'static str {
+    match pos {
+        "名词" | "量词" | "n" | "q" => "NOUN",
+        "专有名词" | "nr" | "ns" | "nt" | "nz" => "PROPN",
+        "代词" | "r" => "PRON",
+        "数词" | "m" => "NUM",
+        "动词" | "v" => "VERB",
+        "形容词" | "a" => "ADJ",
+        "副词" | "d" => "ADV",
+        "介词" | "p" => "ADP",
+        "连词" | "c" => "CCONJ",
+        "助词" | "u" => "PART",
+        "叹词" | "e" => "INTJ",
+        "标点符号" | "w" => "PUNCT",
+        "符号" => "SYM",
+        _ => "X",
+    }
+}
+
+/// Maps the details of a token from `dictionary_kind` onto a Universal Dependencies UPOS tag.
+#[pyfunction]
+#[pyo3(signature = (details, dictionary_kind))]
+pub fn to_upos(details: Vec<String>, dictionary_kind: &str) -> PyResult<&'static str> {
+    let tag_set = PosTagSet::from_name(dictionary_kind)
+        .map_err(|err| PyValueError::new_err(format!("Failed to map to UPOS: {err}")))?;
+
+    Ok(to_upos_tag(tag_set, &details))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipadic_upos() {
+        let upos = |details: &[&str]| to_upos_tag(PosTagSet::Ipadic, details);
+        assert_eq!(upos(&["名詞", "固有名詞", "地域", "一般"]), "PROPN");
+        assert_eq!(upos(&["名詞", "一般"]), "NOUN");
+        assert_eq!(upos(&["動詞", "自立"]), "VERB");
+        assert_eq!(upos(&["動詞", "非自立"]), "AUX");
+        assert_eq!(upos(&["助詞", "格助詞", "一般"]), "ADP");
+        assert_eq!(upos(&["助詞", "接続助詞"]), "SCONJ");
+        assert_eq!(upos(&["記号", "句点"]), "PUNCT");
+        assert_eq!(upos(&["記号", "一般"]), "SYM");
+        assert_eq!(upos(&["UNK"]), "X");
+    }
+
+    #[test]
+    fn test_unidic_upos() {
+        let upos = |details: &[&str]| to_upos_tag(PosTagSet::Unidic, details);
+        assert_eq!(upos(&["名詞", "普通名詞", "一般", "*"]), "NOUN");
+        assert_eq!(upos(&["形状詞", "一般"]), "ADJ");
+        assert_eq!(upos(&["補助記号", "句点"]), "PUNCT");
+        assert_eq!(upos(&["助詞", "準体助詞"]), "SCONJ");
+    }
+
+    #[test]
+    fn test_ko_dic_upos() {
+        let upos = |details: &[&str]| to_upos_tag(PosTagSet::KoDic, details);
+        assert_eq!(upos(&["NNP", "인명"]), "PROPN");
+        assert_eq!(upos(&["VV+EP", "*"]), "VERB");
+        assert_eq!(upos(&["JKS"]), "ADP");
+        assert_eq!(upos(&["SF"]), "PUNCT");
+    }
+
+    #[test]
+    fn test_cc_cedict_upos() {
+        let upos = |details: &[&str]| to_upos_tag(PosTagSet::CcCedict, details);
+        assert_eq!(upos(&["*", "*"]), "X");
+        assert_eq!(
+            upos(&["*", "*", "*", "*", "zhong1 guo2", "中國", "中国", "China"]),
+            "X"
+        );
+        // Words of a user dictionary with a part of speech
+        assert_eq!(upos(&["专有名词", "*", "*", "*", "bei3 jing1"]), "PROPN");
+        assert_eq!(upos(&["v", "*", "*", "*", "zou3"]), "VERB");
+        assert_eq!(upos(&[""]), "X");
+    }
+
+    #[test]
+    fn test_from_schema() {
+        let schema = |fields: &[&str]| Schema::new(fields.iter().map(|f| f.to_string()).collect());
+        assert_eq!(
+            PosTagSet::from_schema(&schema(&["surface", "part_of_speech", "base_form"])),
+            Some(PosTagSet::Ipadic)
+        );
+        assert_eq!(
+            PosTagSet::from_schema(&schema(&["surface", "part_of_speech", "lexeme"])),
+            Some(PosTagSet::Unidic)
+        );
+        assert_eq!(
+            PosTagSet::from_schema(&schema(&["surface", "part_of_speech_tag"])),
+            Some(PosTagSet::KoDic)
+        );
+        assert_eq!(
+            PosTagSet::from_schema(&schema(&["surface", "part_of_speech", "pinyin"])),
+            Some(PosTagSet::CcCedict)
+        );
+        assert_eq!(PosTagSet::from_schema(&schema(&["surface"])), None);
+    }
+}
@@ -1,4 +1,7 @@
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::panic;
+use std::thread;
 
 use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
@@ -74,5 +77,55 @@ pub fn value_to_pydict(py: Python, value: &Value) -> PyResult<Py<PyAny>> {
     }
 }
 
+/// Applies `f` to every item on a pool of scoped threads and returns the results in input order.
+pub fn parallel_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    parallel_chunks(items, |chunk| chunk.iter().map(&f).collect::<Vec<R>>())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Splits `items` into one run of consecutive items per thread, applies `f` to every run
+/// on a pool of scoped threads and returns the results in input order.
+pub fn parallel_chunks<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&[T]) -> R + Sync,
+{
+    let num_threads = thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+        .min(items.len());
+
+    if num_threads <= 1 {
+        return vec![f(items)];
+    }
+
+    let chunk_size = items.len().div_ceil(num_threads);
+    let f = &f;
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || f(chunk)))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| panic::resume_unwind(err))
+            })
+            .collect()
+    })
+}
+
 #[cfg(test)]
 mod tests {}
@@ -1,4 +1,33 @@
-from lindera import Segmenter, Tokenizer, load_dictionary
+import asyncio
+import gc
+import json
+import pickle
+import shutil
+import weakref
+from concurrent.futures import ThreadPoolExecutor
+
+import pytest
+
+from lindera import (
+    Metadata,
+    Mode,
+    Penalty,
+    Segmenter,
+    Token,
+    Tokenizer,
+    TokenizerBuilder,
+    build_dictionary,
+    diff_tokenizations,
+    evaluate,
+    list_character_filters,
+    list_token_filters,
+    load_dictionary,
+    load_user_dictionary,
+    parse_conllu,
+    parse_mecab,
+    parse_wakati,
+    to_upos,
+)
 
 
 def test_tokenize_with_ipadic():
@@ -21,3 +50,852 @@ def test_tokenize_with_ipadic():
     assert tokens[6].text == "うち"
 
     assert len(tokens) == 7
+
+
+def test_tokenize_batch_with_ipadic():
+    dictionary = load_dictionary("embedded://ipadic")
+    segmenter = Segmenter("normal", dictionary)
+    tokenizer = Tokenizer(segmenter)
+
+    texts = ["すもももももももものうち", "関西国際空港", ""]
+
+    # tokenize the texts in parallel
+    results = tokenizer.tokenize_batch(texts)
+
+    assert len(results) == len(texts)
+    for text, tokens in zip(texts, results):
+        assert [token.text for token in tokens] == [token.text for token in tokenizer.tokenize(text)]
+
+    assert [token.text for token in segmenter.segment_batch(texts)[0]] == [
+        "すもも",
+        "も",
+        "もも",
+        "も",
+        "もも",
+        "の",
+        "うち",
+    ]
+
+
+def test_tokenize_shared_across_threads():
+    dictionary = load_dictionary("embedded://ipadic")
+    segmenter = Segmenter("normal", dictionary)
+    tokenizer = Tokenizer(segmenter)
+
+    text = "すもももももももものうち"
+    expected = [token.text for token in tokenizer.tokenize(text)]
+
+    # share one tokenizer across threads
+    with ThreadPoolExecutor(max_workers=4) as executor:
+        results = list(executor.map(tokenizer.tokenize, [text] * 32))
+
+    for tokens in results:
+        assert [token.text for token in tokens] == expected
+
+
+def test_tokenize_nbest_with_ipadic():
+    dictionary = load_dictionary("embedded://ipadic")
+    segmenter = Segmenter("normal", dictionary)
+    tokenizer = Tokenizer(segmenter)
+
+    text = "すもももももも"
+
+    # get the 5 lowest-cost segmentations
+    results = tokenizer.tokenize_nbest(text, 5)
+
+    assert 1 < len(results) <= 5
+
+    costs = [cost for _, cost in results]
+    assert costs == sorted(costs)
+
+    # the first result is the best path
+    best_tokens, _ = results[0]
+    assert [token.text for token in best_tokens] == [token.text for token in tokenizer.tokenize(text)]
+
+    for tokens, _ in results:
+        assert "".join(token.text for token in tokens) == text
+
+    assert len(segmenter.segment_nbest(text, 5)) == len(results)
+
+
+def test_lattice_with_ipadic():
+    dictionary = load_dictionary("embedded://ipadic")
+    segmenter = Segmenter("normal", dictionary)
+
+    text = "すもももももももものうち"
+    lattice = segmenter.lattice(text)
+
+    nodes = {node.id: node for node in lattice.nodes}
+    assert nodes[lattice.best_path[0]].kind == "BOS"
+    assert nodes[lattice.best_path[-1]].kind == "EOS"
+
+    # the best path of the lattice is the segmentation result
+    best_surfaces = [nodes[node_id].surface for node_id in lattice.best_path[1:-1]]
+    assert best_surfaces == [token.text for token in segmenter.segment(text)]
+
+    assert len(lattice.best_path_edges) == len(lattice.best_path) - 1
+    assert set(lattice.best_path_edges) <= set(lattice.edges)
+
+    dot = lattice.to_dot()
+    assert dot.startswith("digraph lattice {")
+
+
+def test_lattice_matches_segment_with_ipadic():
+    # The lattice of this module mirrors the Viterbi search of Lindera, which segment uses
+    dictionary = load_dictionary("embedded://ipadic")
+    texts = [
+        "すもももももももものうち",
+        "関西国際空港限定トートバッグ",
+        "東京スカイツリーの最寄り駅はとうきょうスカイツリー駅です。",
+        "Linderaは形態素解析エンジンです、2024年にv1.0をリリースした。",
+        "🌧のち☀️\tＡＢＣ１２３　ｶﾀｶﾅ\n",
+    ]
+
+    def entries(tokens):
+        return [(token.text, token.byte_start, token.word_id, token.word_cost) for token in tokens]
+
+    for mode in ["normal", "decompose"]:
+        segmenter = Segmenter(mode, dictionary)
+        for text in texts:
+            expected = entries(segmenter.segment(text))
+            tokens, _ = segmenter.segment_nbest(text, 1)[0]
+            assert entries(tokens) == expected
+
+            lattice = segmenter.lattice(text)
+            nodes = {node.id: node for node in lattice.nodes}
+            best_path = [nodes[node_id] for node_id in lattice.best_path]
+            assert [node.surface for node in best_path if node.kind not in ("BOS", "EOS")] == [
+                surface for surface, _, _, _ in expected
+            ]
+
+
+def test_tokenize_iter_with_ipadic(tmp_path):
+    dictionary = load_dictionary("embedded://ipadic")
+    segmenter = Segmenter("normal", dictionary)
+    tokenizer = Tokenizer(segmenter)
+
+    lines = ["すもももももももものうち", "関西国際空港", "東京スカイツリー"]
+    path = tmp_path / "corpus.txt"
+    path.write_text("\n".join(lines) + "\n", encoding="utf-8")
+
+    # tokenize a text file line by line
+    with open(path, encoding="utf-8") as f:
+        results = list(tokenizer.tokenize_iter(f, batch_size=2))
+
+    assert len(results) == len(lines)
+    for line, tokens in zip(lines, results):
+        assert "".join(token.text for token in tokens) == line
+
+    # any iterable of strings is accepted
+    iterator = tokenizer.tokenize_iter(line for line in lines)
+    assert [token.text for token in next(iterator)] == [token.text for token in results[0]]
+
+
+def test_tokenize_async_with_ipadic():
+    dictionary = load_dictionary("embedded://ipadic")
+    segmenter = Segmenter("normal", dictionary)
+    tokenizer = Tokenizer(segmenter)
+
+    texts = ["すもももももももものうち", "関西国際空港"]
+
+    async def run():
+        tokens = await tokenizer.tokenize_async(texts[0])
+        batch = await tokenizer.tokenize_batch_async(texts)
+        return tokens, batch
+
+    tokens, batch = asyncio.run(run())
+
+    assert [token.text for token in tokens] == [token.text for token in tokenizer.tokenize(texts[0])]
+    assert len(batch) == len(texts)
+
+
+def test_token_char_and_utf16_offsets_with_ipadic():
+    dictionary = load_dictionary("embedded://ipadic")
+    segmenter = Segmenter("normal", dictionary)
+    tokenizer = Tokenizer(segmenter, utf16_offsets=True)
+
+    text = "😀すもももももももものうち"
+    tokens = tokenizer.tokenize(text)
+
+    for token in tokens:
+        assert text[token.char_start : token.char_end] == token.text
+
+    encoded = text.encode("utf-16-le")
+    for token in tokens:
+        assert encoded[token.utf16_start * 2 : token.utf16_end * 2].decode("utf-16-le") == token.text
+
+    # UTF-16 offsets are opt-in
+    assert Tokenizer(segmenter).tokenize(text)[0].utf16_start is None
+
+
+def test_token_offsets_with_character_filters():
+    builder = TokenizerBuilder()
+    builder.set_mode("normal")
+    builder.set_dictionary("embedded://ipadic")
+    builder.append_character_filter("unicode_normalize", {"kind": "nfkc"})
+    builder.append_character_filter("mapping", {"mapping": {"リットル": "L"}})
+    tokenizer = builder.build()
+
+    text = "Ｌｉｎｄｅｒａで１０㍑の水を量る"
+    tokens = tokenizer.tokenize(text)
+
+    # offsets refer to the original text, not the filtered text
+    assert tokens[0].text == "Lindera"
+    assert text[tokens[0].char_start : tokens[0].char_end] == "Ｌｉｎｄｅｒａ"
+    assert text.encode("utf-8")[tokens[0].byte_start : tokens[0].byte_end].decode("utf-8") == "Ｌｉｎｄｅｒａ"
+
+    for token in tokens:
+        # spans always fall on character boundaries of the original text
+        text.encode("utf-8")[token.byte_start : token.byte_end].decode("utf-8")
+
+
+def test_tokenizer_to_config_with_ipadic():
+    builder = TokenizerBuilder()
+    builder.set_mode("decompose")
+    builder.set_dictionary("embedded://ipadic")
+    builder.append_character_filter("unicode_normalize", {"kind": "nfkc"})
+    builder.append_token_filter("japanese_stop_tags", {"tags": ["助詞"]})
+    tokenizer = builder.build()
+
+    config = tokenizer.to_config()
+    assert config == builder.to_config()
+    assert config["segmenter"] == {"mode": "decompose", "dictionary": "embedded://ipadic"}
+    assert config["character_filters"] == [{"kind": "unicode_normalize", "args": {"kind": "nfkc"}}]
+    assert config["token_filters"] == [{"kind": "japanese_stop_tags", "args": {"tags": ["助詞"]}}]
+    assert json.loads(tokenizer.to_json(indent=2)) == config
+    assert "japanese_stop_tags" in tokenizer.to_yaml()
+
+    # the exported config rebuilds an equivalent tokenizer
+    text = "ＬｉｎｄｅｒａはＲｕｓｔで書かれた形態素解析器です"
+    rebuilt = Tokenizer.from_config(config)
+    assert [token.text for token in rebuilt.tokenize(text)] == [token.text for token in tokenizer.tokenize(text)]
+
+    # a tokenizer created from a segmenter records the dictionary it was loaded from
+    segmenter = Segmenter("normal", load_dictionary("embedded://ipadic"))
+    assert Tokenizer(segmenter).to_config() == {
+        "segmenter": {"mode": "normal", "dictionary": "embedded://ipadic"},
+        "character_filters": [],
+        "token_filters": [],
+    }
+
+
+def test_pickle_with_ipadic():
+    dictionary = load_dictionary("embedded://ipadic")
+    text = "ＬｉｎｄｅｒａはＲｕｓｔで書かれた形態素解析器です"
+
+    restored_tokenizer = Tokenizer(Segmenter("normal", pickle.loads(pickle.dumps(dictionary))))
+    expected = [repr(token) for token in Tokenizer(Segmenter("normal", dictionary)).tokenize(text)]
+    assert [repr(token) for token in restored_tokenizer.tokenize(text)] == expected
+
+    segmenter = Segmenter("normal", dictionary)
+    restored_segmenter = pickle.loads(pickle.dumps(segmenter))
+    assert [token.text for token in restored_segmenter.segment(text)] == [
+        token.text for token in segmenter.segment(text)
+    ]
+
+    builder = TokenizerBuilder()
+    builder.set_mode("normal")
+    builder.set_dictionary("embedded://ipadic")
+    builder.append_character_filter("unicode_normalize", {"kind": "nfkc"})
+    builder.set_utf16_offsets(True)
+    tokenizer = builder.build()
+
+    restored = pickle.loads(pickle.dumps(tokenizer))
+    assert restored.utf16_offsets
+    assert restored.to_config() == tokenizer.to_config()
+
+    tokens = tokenizer.tokenize(text)
+    restored_tokens = pickle.loads(pickle.dumps(tokens))
+    assert [repr(token) for token in restored.tokenize(text)] == [repr(token) for token in tokens]
+    for token, restored_token in zip(tokens, restored_tokens):
+        assert (restored_token.text, restored_token.details) == (token.text, token.details)
+        assert (restored_token.char_start, restored_token.char_end) == (token.char_start, token.char_end)
+        assert (restored_token.utf16_start, restored_token.utf16_end) == (token.utf16_start, token.utf16_end)
+
+
+def test_pickle_dictionary_by_value(tmp_path):
+    source = tmp_path / "source"
+    source.mkdir()
+    (source / "char.def").write_text("DEFAULT 0 1 0\n", encoding="utf-8")
+    (source / "unk.def").write_text("DEFAULT,0,0,1000,名詞,一般,*,*,*,*,*,*,*\n", encoding="utf-8")
+    (source / "matrix.def").write_text("1 1\n0 0 0\n", encoding="utf-8")
+    (source / "lex.csv").write_text(
+        "東京,0,0,100,名詞,固有名詞,地域,一般,*,*,東京,トウキョウ,トーキョー\n"
+        "に,0,0,100,助詞,格助詞,一般,*,*,*,に,ニ,ニ\n",
+        encoding="utf-8",
+    )
+    output = tmp_path / "dictionary"
+    build_dictionary(str(source), str(output), Metadata())
+
+    data = pickle.dumps(load_dictionary(str(output)))
+    # The dictionary no longer needs to exist where it was loaded from
+    shutil.rmtree(output)
+    restored = pickle.loads(data)
+
+    # A restored dictionary is pickled again from memory
+    restored = pickle.loads(pickle.dumps(restored))
+
+    tokens = Tokenizer(Segmenter("normal", restored)).tokenize("東京に")
+    assert [token.text for token in tokens] == ["東京", "に"]
+    assert tokens[0].details[7] == "トウキョウ"
+    assert tokens[0].word_cost == 100
+
+
+def test_static_constructors_with_ipadic(tmp_path):
+    yaml = """
+segmenter:
+  mode: normal
+  dictionary: embedded://ipadic
+token_filters:
+  - kind: japanese_stop_tags
+    args:
+      tags:
+        - 助詞
+"""
+    config = {
+        "segmenter": {"mode": "normal", "dictionary": "embedded://ipadic"},
+        "token_filters": [{"kind": "japanese_stop_tags", "args": {"tags": ["助詞"]}}],
+    }
+    config_file = tmp_path / "lindera.yml"
+    config_file.write_text(yaml, encoding="utf-8")
+
+    text = "関西国際空港限定トートバッグ"
+    expected = ["関西国際空港", "限定", "トートバッグ"]
+
+    tokenizers = [
+        Tokenizer.from_yaml(yaml),
+        Tokenizer.from_json(json.dumps(config)),
+        Tokenizer.from_dict(config),
+        Tokenizer.from_path(config_file),
+        Tokenizer.from_path(str(config_file)),
+    ]
+    for tokenizer in tokenizers:
+        assert [token.text for token in tokenizer.tokenize(text)] == expected
+    assert Tokenizer.from_dict(config, utf16_offsets=True).utf16_offsets
+
+    segmenter_config = {"mode": "normal", "dictionary": "embedded://ipadic"}
+    segmenters = [
+        Segmenter.from_yaml(yaml),
+        Segmenter.from_json(json.dumps(segmenter_config)),
+        Segmenter.from_dict(segmenter_config),
+        Segmenter.from_dict(config),
+        Segmenter.from_path(config_file),
+    ]
+    for segmenter in segmenters:
+        assert [token.text for token in segmenter.segment(text)] == expected
+
+    assert [token.text for token in TokenizerBuilder.from_file(str(config_file)).build().tokenize(text)] == expected
+
+
+def test_mode_and_penalty_with_ipadic():
+    dictionary = load_dictionary("embedded://ipadic")
+    text = "関西国際空港限定トートバッグ"
+
+    segmenter = Segmenter(Mode.Decompose, dictionary)
+    assert segmenter.mode == Mode.Decompose
+    assert segmenter.penalty.kanji_penalty_length_threshold == 2
+    assert [token.text for token in segmenter.segment(text)][:3] == ["関西", "国際", "空港"]
+
+    # a high kanji threshold disables the decomposition of kanji compounds
+    penalty = Penalty(kanji_penalty_length_threshold=10)
+    segmenter = Segmenter(Mode.Decompose, dictionary, penalty=penalty)
+    assert segmenter.penalty.kanji_penalty_length_threshold == 10
+    assert [token.text for token in segmenter.segment(text)][0] == "関西国際空港"
+
+    builder = TokenizerBuilder()
+    builder.set_mode(Mode.Decompose, penalty)
+    builder.set_dictionary("embedded://ipadic")
+    tokenizer = builder.build()
+    assert tokenizer.mode == Mode.Decompose
+    assert tokenizer.penalty.kanji_penalty_length_threshold == 10
+    assert [token.text for token in tokenizer.tokenize(text)][0] == "関西国際空港"
+    assert tokenizer.to_config()["segmenter"]["mode"]["decompose"]["kanji_penalty_length_threshold"] == 10
+
+    assert Segmenter("normal", dictionary).mode == Mode.Normal
+    assert Segmenter("normal", dictionary).penalty is None
+
+
+def expand_abbreviations(text):
+    return text.replace("ＭＳ", "マイクロソフト")
+
+
+def drop_symbols(tokens):
+    return [token for token in tokens if token.details[0] != "記号"]
+
+
+def test_python_filters_with_ipadic():
+    builder = TokenizerBuilder()
+    builder.set_mode("normal")
+    builder.set_dictionary("embedded://ipadic")
+    builder.append_character_filter(expand_abbreviations)
+    builder.append_character_filter("unicode_normalize", {"kind": "nfkc"})
+    builder.append_token_filter(drop_symbols)
+    builder.append_token_filter("japanese_base_form")
+    tokenizer = builder.build()
+
+    text = "ＭＳの製品を買った！"
+    tokens = tokenizer.tokenize(text)
+    assert [token.text for token in tokens] == ["マイクロソフト", "の", "製品", "を", "買う", "た"]
+
+    # offsets still refer to the original text
+    assert text[tokens[0].char_start : tokens[0].char_end] == "ＭＳ"
+    assert text[tokens[2].char_start : tokens[2].char_end] == "製品"
+
+    # Python filters are pickled by reference
+    restored = pickle.loads(pickle.dumps(tokenizer))
+    assert [token.text for token in restored.tokenize(text)] == [token.text for token in tokens]
+
+
+def test_python_token_filter_word_ids_with_ipadic():
+    def merge_first_two(tokens):
+        first, second = tokens[0], tokens[1]
+        merged = Token(first.text + second.text, first.byte_start, second.byte_end, 0, 2, ["名詞"])
+        return [merged] + tokens[2:]
+
+    builder = TokenizerBuilder()
+    builder.set_dictionary("embedded://ipadic")
+    builder.append_token_filter(merge_first_two)
+    tokens = builder.build().tokenize("東京タワーに行く")
+
+    # A token built by the filter has no dictionary entry, the others keep theirs
+    assert tokens[0].is_unknown
+    assert tokens[0].word_id is None
+    assert tokens[0].word_cost is None
+    assert not tokens[1].is_unknown
+    assert tokens[1].word_id is not None
+
+
+def test_python_filter_cycle_is_collected_with_ipadic():
+    class Filter:
+        def __call__(self, tokens):
+            return tokens
+
+    func = Filter()
+    builder = TokenizerBuilder()
+    builder.set_dictionary("embedded://ipadic")
+    builder.append_token_filter(func)
+    tokenizer = builder.build()
+    # The callable refers back to the tokenizer and the builder that hold it
+    func.tokenizer = tokenizer
+    func.builder = builder
+    ref = weakref.ref(func)
+
+    del func, tokenizer, builder
+    gc.collect()
+    assert ref() is None
+
+
+def test_filter_registry_with_ipadic():
+    character_filters = {filter["name"]: filter for filter in list_character_filters()}
+    iteration_mark = character_filters["japanese_iteration_mark"]
+    assert iteration_mark["dictionaries"] is None
+    assert iteration_mark["args"][0] == {
+        "name": "normalize_kanji",
+        "type": "bool",
+        "required": False,
+        "default": False,
+    }
+    assert character_filters["unicode_normalize"]["args"][0]["choices"] == ["nfc", "nfd", "nfkc", "nfkd"]
+
+    token_filters = {filter["name"]: filter for filter in list_token_filters()}
+    assert "ipadic" in token_filters["japanese_stop_tags"]["dictionaries"]
+    assert token_filters["korean_stop_tags"]["dictionaries"] == ["ko-dic"]
+    assert token_filters["lowercase"]["args"] == []
+
+    builder = TokenizerBuilder()
+    builder.set_dictionary("embedded://ipadic")
+
+    # args are checked when the filter is appended
+    with pytest.raises(ValueError, match="'normalize_kanji' must be bool"):
+        builder.append_character_filter("japanese_iteration_mark", {"normalize_kanji": "true"})
+    with pytest.raises(ValueError, match="must be one of nfc, nfd, nfkc, nfkd"):
+        builder.append_character_filter("unicode_normalize", {"kind": "NFKC"})
+    with pytest.raises(ValueError, match="missing required argument 'tags'"):
+        builder.append_token_filter("japanese_stop_tags")
+    with pytest.raises(ValueError, match="unknown argument 'minimum'"):
+        builder.append_token_filter("length", {"minimum": 2})
+    with pytest.raises(ValueError, match="unknown token filter 'lowercasee'"):
+        builder.append_token_filter("lowercasee")
+
+    builder.append_character_filter("japanese_iteration_mark", {"normalize_kanji": True, "normalize_kana": True})
+    tokenizer = builder.build()
+    assert "".join(token.text for token in tokenizer.tokenize("時々こゝろ")) == "時時こころ"
+
+
+def test_edit_builder_pipeline_with_ipadic(tmp_path):
+    config_path = tmp_path / "lindera.yml"
+    config_path.write_text(
+        """
+segmenter:
+  mode: normal
+  dictionary: embedded://ipadic
+character_filters:
+  - kind: unicode_normalize
+    args:
+      kind: nfkc
+token_filters:
+  - kind: japanese_stop_tags
+    args:
+      tags: [助詞, 記号]
+  - kind: lowercase
+"""
+    )
+
+    builder = TokenizerBuilder.from_file(str(config_path))
+    assert builder.character_filters() == [{"kind": "unicode_normalize", "args": {"kind": "nfkc"}}]
+    assert [filter["kind"] for filter in builder.token_filters()] == ["japanese_stop_tags", "lowercase"]
+
+    builder.insert_token_filter(0, "japanese_base_form")
+    builder.insert_token_filter(-1, "length", {"min": 2})
+    assert [filter["kind"] for filter in builder.token_filters()] == [
+        "japanese_base_form",
+        "japanese_stop_tags",
+        "length",
+        "lowercase",
+    ]
+
+    builder.set_token_filter_args("japanese_stop_tags", {"tags": ["助詞", "助動詞", "記号"]})
+    assert builder.token_filters()[1]["args"] == {"tags": ["助詞", "助動詞", "記号"]}
+    with pytest.raises(ValueError, match="must be list\\[str\\]"):
+        builder.set_token_filter_args(1, {"tags": "助詞"})
+
+    builder.remove_token_filter("lowercase")
+    builder.remove_token_filter(-1)
+    assert [filter["kind"] for filter in builder.token_filters()] == ["japanese_base_form", "japanese_stop_tags"]
+    with pytest.raises(IndexError):
+        builder.remove_token_filter(5)
+    with pytest.raises(ValueError, match="No token filter 'lowercase'"):
+        builder.remove_token_filter("lowercase")
+
+    builder.clear_character_filters()
+    assert builder.character_filters() == []
+
+    tokenizer = builder.build()
+    assert [token.text for token in tokenizer.tokenize("関西国際空港に行った。")] == ["関西国際空港", "行く"]
+
+
+def test_split_sentences_with_ipadic():
+    tokenizer = Tokenizer(Segmenter("normal", load_dictionary("embedded://ipadic")), utf16_offsets=True)
+
+    text = "今日は晴れです。「本当？」\n明日は🌧…かも"
+    sentences = tokenizer.split_sentences(text)
+    assert [sentence.text for sentence in sentences] == ["今日は晴れです。", "「本当？」", "明日は🌧…かも"]
+    for sentence in sentences:
+        assert text[sentence.char_start : sentence.char_end] == sentence.text
+        assert text.encode("utf-8")[sentence.byte_start : sentence.byte_end].decode("utf-8") == sentence.text
+    assert sentences[2].utf16_end == len(text.encode("utf-16-le")) // 2
+
+    # A quote followed by more text on the same line stays in the sentence
+    sentences = tokenizer.split_sentences("「はい。」と答えた！次へ。")
+    assert [sentence.text for sentence in sentences] == ["「はい。」と答えた！", "次へ。"]
+
+    assert [sentence.text for sentence in tokenizer.split_sentences("A. B\nC", terminators=["."], split_on_newlines=False)] == [
+        "A.",
+        "B\nC",
+    ]
+
+    groups = tokenizer.tokenize_sentences(text)
+    assert len(groups) == 3
+    assert [token.text for token in groups[0]] == ["今日", "は", "晴れ", "です", "。"]
+    for tokens in groups:
+        assert tokens[0].position == 0
+        for token in tokens:
+            assert text[token.char_start : token.char_end] == token.text
+
+    restored = pickle.loads(pickle.dumps(sentences[0]))
+    assert (restored.text, restored.byte_start, restored.char_end) == ("今日は晴れです。", 0, 8)
+
+
+def test_to_conllu_with_ipadic():
+    tokenizer = Tokenizer(Segmenter("normal", load_dictionary("embedded://ipadic")))
+
+    conllu = tokenizer.to_conllu("東京に行った。 明日も🌧")
+    blocks = conllu.split("\n\n")
+    assert blocks[-1] == ""
+    assert len(blocks) == 3
+
+    lines = blocks[0].split("\n")
+    assert lines[:2] == ["# sent_id = 1", "# text = 東京に行った。"]
+    rows = [line.split("\t") for line in lines[2:]]
+    assert all(len(row) == 10 for row in rows)
+    assert [row[0] for row in rows] == ["1", "2", "3", "4", "5"]
+    assert rows[0][1:5] == ["東京", "東京", "PROPN", "名詞-固有名詞-地域-一般"]
+    assert rows[2][1:4] == ["行っ", "行く", "VERB"]
+    assert rows[0][9] == "SpaceAfter=No|TokenRange=0:2"
+    # The sentence is followed by a space
+    assert rows[-1][9] == "TokenRange=6:7"
+
+    rows = [line.split("\t") for line in blocks[1].split("\n")[2:]]
+    assert rows[-1][1:5] == ["🌧", "🌧", "X", "UNK"]
+
+
+def test_format_mecab_with_ipadic():
+    tokenizer = Tokenizer(Segmenter("normal", load_dictionary("embedded://ipadic")))
+
+    output = tokenizer.format_mecab("東京に行く")
+    assert output.splitlines() == [
+        "東京\t名詞,固有名詞,地域,一般,*,*,東京,トウキョウ,トーキョー",
+        "に\t助詞,格助詞,一般,*,*,*,に,ニ,ニ",
+        "行く\t動詞,自立,*,*,五段・カ行促音便,基本形,行く,イク,イク",
+        "EOS",
+    ]
+    assert tokenizer.format_wakati("東京に行く") == "東京 に 行く\n"
+
+    # Every line of the text ends with EOS, as in MeCab
+    output = tokenizer.format_mecab("東京に\n行く\n")
+    assert [line.split("\t")[0] for line in output.splitlines()] == ["東京", "に", "EOS", "行く", "EOS"]
+    assert [[token.text for token in sentence] for sentence in parse_mecab(output)] == [["東京", "に"], ["行く"]]
+    assert tokenizer.format_wakati("東京に\n行く") == "東京 に\n行く\n"
+
+    output = tokenizer.format_mecab("東京に行く", node_format="%m/%f[reading]/%f[0,1] ", eos_format="\n")
+    assert output == "東京/トウキョウ/名詞,固有名詞 に/ニ/助詞,格助詞 行く/イク/動詞,自立 \n"
+
+    # MeCab style escapes in templates passed as raw strings
+    assert tokenizer.format_mecab("東京", node_format=r"%m\t%f[6]\n") == "東京\t東京\nEOS\n"
+
+    with pytest.raises(ValueError, match="unknown field 'yomi'"):
+        tokenizer.format_mecab("東京", node_format="%f[yomi]")
+
+
+def test_tokenize_to_arrow_with_ipadic():
+    pa = pytest.importorskip("pyarrow")
+    tokenizer = Tokenizer(Segmenter("normal", load_dictionary("embedded://ipadic")))
+
+    texts = ["東京に行く", "", "すもも"]
+    batch = tokenizer.tokenize_to_arrow(texts, fields=["part_of_speech", "reading"])
+    assert isinstance(batch, pa.RecordBatch)
+    assert batch.schema.names == ["doc_id", "surface", "byte_start", "byte_end", "position", "part_of_speech", "reading"]
+    assert batch.column("doc_id").to_pylist() == [0, 0, 0, 2]
+    assert batch.column("surface").to_pylist() == ["東京", "に", "行く", "すもも"]
+    assert batch.column("byte_start").to_pylist() == [0, 6, 9, 0]
+    assert batch.column("reading").to_pylist() == ["トウキョウ", "ニ", "イク", "スモモ"]
+
+    with pytest.raises(ValueError, match="unknown field 'yomi'"):
+        tokenizer.tokenize_to_arrow(texts, fields=["yomi"])
+
+
+def test_tokenize_to_numpy_with_ipadic():
+    np = pytest.importorskip("numpy")
+    tokenizer = Tokenizer(Segmenter("normal", load_dictionary("embedded://ipadic")))
+
+    arrays = tokenizer.tokenize_to_numpy(["東京に行く", "すもも"], fields=["part_of_speech"])
+    assert list(arrays) == ["doc_id", "surface", "byte_start", "byte_end", "position", "part_of_speech"]
+    assert arrays["doc_id"].dtype == np.int64
+    assert arrays["doc_id"].tolist() == [0, 0, 0, 1]
+    assert arrays["surface"].tolist() == ["東京", "に", "行く", "すもも"]
+    assert arrays["part_of_speech"].tolist() == ["名詞", "助詞", "動詞", "名詞"]
+    assert arrays["position"].tolist() == [0, 1, 2, 0]
+
+    # Fixed width string columns are as wide as their longest value
+    assert arrays["surface"].dtype == np.dtype("<U3")
+
+    arrays = tokenizer.tokenize_to_numpy(["東京に行く", "すもも"], fields=["part_of_speech"], string_dtype="object")
+    assert arrays["surface"].dtype == np.dtype("O")
+    assert arrays["surface"].tolist() == ["東京", "に", "行く", "すもも"]
+    assert arrays["doc_id"].tolist() == [0, 0, 0, 1]
+
+    with pytest.raises(ValueError, match="unknown string dtype"):
+        tokenizer.tokenize_to_numpy(["東京"], string_dtype="bytes")
+
+
+def test_named_details_with_ipadic():
+    tokenizer = Tokenizer(Segmenter("normal", load_dictionary("embedded://ipadic")))
+
+    tokens = tokenizer.tokenize("東京に行った")
+    assert tokens[0].get("part_of_speech") == "名詞"
+    assert tokens[0].get("reading") == "トウキョウ"
+    assert tokens[0].get("surface") == "東京"
+    assert (tokens[2].pos, tokens[2].base_form, tokens[2].reading) == ("動詞", "行く", "イッ")
+    assert tokens[0].features["part_of_speech_subcategory_1"] == "固有名詞"
+    assert list(tokens[0].features) == tokens[0].schema.fields[4:]
+
+    with pytest.raises(KeyError, match="unknown field 'yomi'"):
+        tokens[0].get("yomi")
+
+    # Unknown words have fewer details than the schema
+    unknown = tokenizer.tokenize("🌧")[0]
+    assert unknown.details == ["UNK"]
+    assert unknown.get("reading") is None
+    assert unknown.features == {"part_of_speech": "UNK"}
+
+    # The schema survives pickling
+    restored = pickle.loads(pickle.dumps(tokens[0]))
+    assert restored.reading == "トウキョウ"
+
+    # Tokens created by hand have no schema
+    token = Token("東京", 0, 6, 0, 1, ["名詞"])
+    assert token.pos is None
+    assert token.features == {}
+
+    # Character offsets are derived when the token starts the text, and unknown otherwise
+    assert (token.char_start, token.char_end) == (0, 2)
+    token = Token("京", 3, 6, 1, 1, ["名詞"])
+    assert (token.char_start, token.char_end) == (None, None)
+    token = Token("京", 3, 6, 1, 1, ["名詞"], char_start=1)
+    assert token.char_end == 2
+
+
+def test_word_entry_with_ipadic():
+    segmenter = Segmenter("normal", load_dictionary("embedded://ipadic"))
+    tokenizer = Tokenizer(segmenter)
+
+    tokens = tokenizer.tokenize("東京に🌧")
+    known = tokens[0]
+    assert known.word_id is not None
+    assert not known.is_unknown
+    assert not known.is_user_dictionary
+    assert isinstance(known.word_cost, int)
+    assert known.left_context_id is not None and known.right_context_id is not None
+
+    # The entry of a known word agrees with its node in the lattice
+    node = next(node for node in segmenter.lattice("東京に🌧").nodes if node.word_id == known.word_id)
+    assert (node.word_cost, node.left_context_id, node.right_context_id) == (
+        known.word_cost,
+        known.left_context_id,
+        known.right_context_id,
+    )
+
+    # An unknown word reports the entry of the unknown dictionary, DEFAULT,5,5,4769 in unk.def
+    unknown = tokens[-1]
+    assert unknown.is_unknown
+    assert unknown.word_id is None
+    assert (unknown.word_cost, unknown.left_context_id, unknown.right_context_id) == (4769, 5, 5)
+    lattice = segmenter.lattice("東京に🌧")
+    node = next(node for node in lattice.nodes if node.id in lattice.best_path and node.surface == "🌧")
+    assert (node.word_cost, node.left_context_id, node.right_context_id) == (4769, 5, 5)
+    assert segmenter.segment("🌧")[0].word_cost == 4769
+
+    # OOV rate
+    assert sum(token.is_unknown for token in tokens) / len(tokens) == 1 / 3
+
+    restored = pickle.loads(pickle.dumps(known))
+    assert (restored.word_id, restored.word_cost, restored.is_unknown) == (known.word_id, known.word_cost, False)
+
+
+def test_upos_with_ipadic():
+    tokenizer = Tokenizer(Segmenter("normal", load_dictionary("embedded://ipadic")))
+
+    tokens = tokenizer.tokenize("東京に行く。🌧")
+    assert [token.upos for token in tokens] == ["PROPN", "ADP", "VERB", "PUNCT", "X"]
+
+    assert to_upos(tokens[0].details, "ipadic") == "PROPN"
+    assert to_upos(["名詞", "普通名詞", "一般", "*"], "unidic") == "NOUN"
+    assert to_upos(["VV+EP", "*"], "ko-dic") == "VERB"
+    with pytest.raises(ValueError):
+        to_upos(tokens[0].details, "mecab")
+
+    assert Token("東京", 0, 6, 0, 1, ["名詞", "固有名詞"]).upos is None
+
+
+def test_parse_gold_with_ipadic():
+    tokenizer = Tokenizer(Segmenter("normal", load_dictionary("embedded://ipadic")))
+    text = "東京に行った。明日も🌧"
+    expected = tokenizer.tokenize_sentences(text)
+    schema = expected[0][0].schema
+
+    def spans(sentences):
+        return [[(token.text, token.position) for token in tokens] for tokens in sentences]
+
+    gold = parse_mecab(tokenizer.format_mecab("東京に行った。") + tokenizer.format_mecab("明日も🌧"), schema=schema)
+    assert spans(gold) == spans(expected)
+    assert [token.details for token in gold[0]] == [token.details for token in expected[0]]
+    assert (gold[0][1].char_start, gold[0][1].char_end, gold[0][1].byte_start) == (2, 3, 6)
+    assert gold[0][0].upos == "PROPN"
+    assert gold[0][0].get("reading") == "トウキョウ"
+
+    gold = parse_conllu(tokenizer.to_conllu(text))
+    assert spans(gold) == spans(expected)
+    assert gold[0][0].details[:3] == ["東京", "PROPN", "名詞-固有名詞-地域-一般"]
+    assert gold[0][0].upos == "PROPN"
+    assert gold[0][0].get("upos") == "PROPN"
+
+    gold = parse_wakati(tokenizer.format_wakati("東京に行った。") + tokenizer.format_wakati("明日も🌧"))
+    assert spans(gold) == spans(expected)
+
+    with pytest.raises(ValueError):
+        parse_mecab("東京 名詞\nEOS\n")
+    with pytest.raises(ValueError):
+        parse_conllu("1\t東京\n")
+
+
+def test_evaluate_with_ipadic():
+    tokenizer = Tokenizer(Segmenter("normal", load_dictionary("embedded://ipadic")))
+
+    # The output of the tokenizer scores perfectly against itself
+    gold = parse_conllu(tokenizer.to_conllu("東京に行った。明日も晴れ"))
+    evaluation = evaluate(tokenizer, gold, field="upos")
+    assert (evaluation.precision, evaluation.recall, evaluation.f1) == (1.0, 1.0, 1.0)
+    assert evaluation.field_accuracy == 1.0
+    assert evaluation.mismatches == []
+
+    gold = parse_wakati("東 京 に 行った 。\n")
+    evaluation = evaluate(tokenizer, gold)
+    assert (evaluation.gold_count, evaluation.system_count, evaluation.correct_count) == (5, 5, 2)
+    assert evaluation.precision == evaluation.recall == 0.4
+    assert evaluation.f1 == pytest.approx(0.4)
+    assert evaluation.field_accuracy is None
+    mismatch = evaluation.mismatches[0]
+    assert (mismatch.index, mismatch.text) == (0, "東京に行った。")
+    assert [(token.text, token.byte_start, token.byte_end) for token in mismatch.missing] == [
+        ("東", 0, 3),
+        ("京", 3, 6),
+        ("行った", 9, 18),
+    ]
+    assert [(token.text, token.byte_start, token.byte_end) for token in mismatch.extra] == [
+        ("東京", 0, 6),
+        ("行っ", 9, 15),
+        ("た", 15, 18),
+    ]
+
+    schema = tokenizer.tokenize("東京")[0].schema
+    gold = parse_mecab("東京\t名詞,一般\nに\t助詞,格助詞\nEOS\n", schema=schema)
+    evaluation = evaluate(tokenizer, gold, field="part_of_speech_subcategory_1")
+    assert evaluation.field_accuracy == 0.5
+    gold_token, system_token = evaluation.mismatches[0].field_mismatches[0]
+    assert (gold_token.text, gold_token.details[1], system_token.details[1]) == ("東京", "一般", "固有名詞")
+
+    with pytest.raises(ValueError):
+        evaluate(tokenizer, gold, field="no_such_field")
+
+    # Fields of gold tokens without a schema cannot be compared
+    gold = parse_mecab("東京\t名詞,一般\nEOS\n")
+    with pytest.raises(ValueError, match="no schema"):
+        evaluate(tokenizer, gold, field="part_of_speech")
+    assert evaluate(tokenizer, gold).correct_count == 1
+
+
+def test_diff_tokenizations_with_ipadic(tmp_path):
+    dictionary = load_dictionary("embedded://ipadic")
+    normal = Tokenizer(Segmenter("normal", dictionary))
+    decompose = Tokenizer(Segmenter("decompose", dictionary))
+
+    texts = ["関西国際空港限定トートバッグ", "東京に行く"]
+    diff = diff_tokenizations(normal, decompose, texts)
+    assert diff.text_count == 2
+    assert diff.segmentation_diff_count == len(diff.spans) >= 1
+    span = diff.spans[0]
+    assert (span.index, span.text, span.byte_start, span.byte_end) == (0, "関西国際空港", 0, 18)
+    assert (span.char_start, span.char_end) == (0, 6)
+    assert [token.text for token in span.tokens_a] == ["関西国際空港"]
+    assert [token.text for token in span.tokens_b] == ["関西", "国際", "空港"]
+    assert span.is_segmentation_diff
+    assert all(span.index == 0 for span in diff.spans)
+
+    # The same segmentation with a different reading from a user dictionary
+    path = tmp_path / "userdic.csv"
+    path.write_text("東京,カスタム名詞,トーキョー\n", encoding="utf-8")
+    user_dictionary = load_user_dictionary(str(path), dictionary.metadata())
+    custom = Tokenizer(Segmenter("normal", dictionary, user_dictionary))
+
+    diff = diff_tokenizations(normal, custom, ["東京に行く"], fields=["reading", "upos"])
+    assert diff.segmentation_diff_count == 0
+    assert diff.field_diff_counts["reading"] == 1
+    span = diff.spans[0]
+    assert span.text == "東京"
+    assert "reading" in span.fields
+    assert (span.tokens_a[0].reading, span.tokens_b[0].reading) == ("トウキョウ", "トーキョー")
+
+    assert diff_tokenizations(normal, normal, texts, fields=["reading"]).spans == []
+
+    with pytest.raises(ValueError):
+        diff_tokenizations(normal, decompose, texts, fields=["no_such_field"])